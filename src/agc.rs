@@ -0,0 +1,111 @@
+/// Automatic Gain Control configuration, defaulting to the chip reset values.
+#[derive(Clone, Copy, Debug)]
+pub struct AgcConfig {
+    pub(crate) max_dvga_gain: u8,
+    pub(crate) max_lna_gain: u8,
+    pub(crate) magn_target: u8,
+    pub(crate) lna_priority: bool,
+    pub(crate) carrier_sense_rel_thr: u8,
+    pub(crate) carrier_sense_abs_thr: i8,
+    pub(crate) hyst_level: u8,
+    pub(crate) wait_time: u8,
+    pub(crate) freeze: u8,
+    pub(crate) filter_length: u8,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        AgcConfig {
+            max_dvga_gain: 0,
+            max_lna_gain: 0,
+            magn_target: 3,
+            lna_priority: true,
+            carrier_sense_rel_thr: 0,
+            carrier_sense_abs_thr: 0,
+            hyst_level: 2,
+            wait_time: 1,
+            freeze: 0,
+            filter_length: 1,
+        }
+    }
+}
+
+impl AgcConfig {
+    /// Number of highest DVGA gain settings to exclude, 0 to 3.
+    pub fn max_dvga_gain(mut self, steps: u8) -> Self {
+        self.max_dvga_gain = steps;
+        self
+    }
+
+    /// Maximum LNA + LNA 2 gain, in steps of roughly 2.6 to 3 dB below maximum, 0 to 7.
+    pub fn max_lna_gain(mut self, steps: u8) -> Self {
+        self.max_lna_gain = steps;
+        self
+    }
+
+    /// Target amplitude from the channel filter, from 24 dB (0) to 42 dB (7).
+    pub fn magn_target(mut self, target: u8) -> Self {
+        self.magn_target = target;
+        self
+    }
+
+    /// Decrease the LNA gain before the LNA 2 gain (true), or the other way around (false).
+    pub fn lna_priority(mut self, lna_first: bool) -> Self {
+        self.lna_priority = lna_first;
+        self
+    }
+
+    /// Relative RSSI increase asserting carrier sense, disabled (0), 6 dB (1), 10 dB (2) or 14 dB (3).
+    pub fn carrier_sense_rel_thr(mut self, thr: u8) -> Self {
+        self.carrier_sense_rel_thr = thr;
+        self
+    }
+
+    /// Absolute RSSI threshold asserting carrier sense, in dB relative to the magnitude
+    /// target, -7 to 7, or -8 to disable.
+    pub fn carrier_sense_abs_thr(mut self, db: i8) -> Self {
+        self.carrier_sense_abs_thr = db;
+        self
+    }
+
+    /// Hysteresis on the magnitude deviation, from none (0) to large (3).
+    pub fn hyst_level(mut self, level: u8) -> Self {
+        self.hyst_level = level;
+        self
+    }
+
+    /// Channel filter samples to wait after a gain adjustment, 8 (0), 16, 24 or 32 (3).
+    pub fn wait_time(mut self, time: u8) -> Self {
+        self.wait_time = time;
+        self
+    }
+
+    /// When the gain is frozen, from normal operation (0) to freezing both analog and digital gain (3).
+    pub fn freeze(mut self, freeze: u8) -> Self {
+        self.freeze = freeze;
+        self
+    }
+
+    /// Channel filter samples averaged for FSK/MSK, or the decision boundary for OOK/ASK, 0 to 3.
+    pub fn filter_length(mut self, length: u8) -> Self {
+        self.filter_length = length;
+        self
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let two_bit = [
+            self.max_dvga_gain,
+            self.carrier_sense_rel_thr,
+            self.hyst_level,
+            self.wait_time,
+            self.freeze,
+            self.filter_length,
+        ]
+        .iter()
+        .all(|&value| value <= 3);
+        two_bit
+            && self.max_lna_gain <= 7
+            && self.magn_target <= 7
+            && (-8..=7).contains(&self.carrier_sense_abs_thr)
+    }
+}
@@ -0,0 +1,178 @@
+//! Async reception and transmission, awaiting GDO0 instead of polling the chip.
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use hal::digital::{InputPin, OutputPin};
+use hal::spi::SpiDevice;
+
+use crate::lowlevel::registers::*;
+use crate::lowlevel::types::*;
+use crate::{Cc1101, Error, RadioMode, ReceivedPacket};
+
+impl<SPI, CS, GDO0, GDO2, SpiE, GpioE> Cc1101<SPI, CS, GDO0, GDO2>
+where
+    SPI: SpiDevice<u8, Error = SpiE>,
+    CS: OutputPin<Error = GpioE>,
+    GDO0: InputPin<Error = GpioE> + Wait,
+    GDO2: InputPin<Error = GpioE>,
+{
+    /// Set radio in Receive/Transmit/Idle mode, awaiting GDO0 instead of polling the machine
+    /// state.
+    ///
+    /// GDO0 signals LNA_PD or PA_PD while the mode is entered, which goes low once in RX or TX.
+    /// A PLL which fails to lock never gets there, so use a timeout of the executor. The GDO0
+    /// configuration is restored afterwards.
+    pub async fn set_radio_mode_async(
+        &mut self,
+        radio_mode: RadioMode,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let (cfg, strobe) = match radio_mode {
+            RadioMode::Receive => (GdoCfg::LNA_PD, Command::SRX),
+            RadioMode::Transmit => (GdoCfg::PA_PD, Command::STX),
+            RadioMode::Idle | RadioMode::FastReady => return self.set_radio_mode(radio_mode),
+        };
+        self.leave_for_synthesizer_state(None)?;
+
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        self.set_gdo0_config(cfg)?;
+        self.0.write_strobe(strobe)?;
+        let result = self.0.gdo0.wait_for_low().await.map_err(Error::Gpio);
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        result
+    }
+
+    /// Receive a packet, awaiting the end of packet on GDO0 instead of polling the RX FIFO.
+    ///
+    /// The radio must be in RX, see `set_radio_mode_async`. GDO0 signals SYNC_WORD while
+    /// receiving, and its configuration is restored afterwards.
+    pub async fn receive_async(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        self.set_gdo0_config(GdoCfg::SYNC_WORD)?;
+        let result = self.await_packet(addr, buf).await;
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        result
+    }
+
+    async fn await_packet(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        loop {
+            // A packet received before this was polled has no edges left to await.
+            if let Some(packet) = self.try_receive(addr, buf)? {
+                return Ok(packet);
+            }
+            self.0.gdo0.wait_for_high().await.map_err(Error::Gpio)?;
+            self.0.gdo0.wait_for_low().await.map_err(Error::Gpio)?;
+        }
+    }
+
+    /// Transmit a packet, awaiting the end of packet on GDO0 instead of polling the machine
+    /// state.
+    ///
+    /// The payload must match the configured `PacketLength`, see `transmit`. GDO0 signals
+    /// SYNC_WORD while transmitting, and its configuration is restored afterwards.
+    pub async fn transmit_async<D: DelayNs>(
+        &mut self,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        let variable = self.validate_payload(None, payload)?;
+        self.flush_tx()?;
+        delay.delay_us(100).await;
+        let written = self.write_payload(None, payload, variable)?;
+
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        self.set_gdo0_config(GdoCfg::SYNC_WORD)?;
+        self.0.write_strobe(Command::STX)?;
+        let result = self.await_sent().await;
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        result.map(|_| written)
+    }
+
+    async fn await_sent(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        // A packet sent before this was polled has no edges left to await.
+        if self.tx_bytes_available()? > 0 {
+            self.0.gdo0.wait_for_high().await.map_err(Error::Gpio)?;
+            self.0.gdo0.wait_for_low().await.map_err(Error::Gpio)?;
+        }
+        // SYNC_WORD also de-asserts on TX FIFO underflow.
+        self.tx_bytes_available()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lowlevel::registers::*;
+    use crate::lowlevel::types::*;
+    use crate::mock;
+    use crate::RadioMode;
+
+    #[test]
+    fn test_set_radio_mode_async() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        mock::block_on(cc1101.set_radio_mode_async(RadioMode::Receive)).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.marcstate, MachineState::RX.value());
+        assert_eq!(chip.register(Config::IOCFG0), 0x3F);
+    }
+
+    #[test]
+    fn test_receive_async() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        mock::block_on(cc1101.set_radio_mode_async(RadioMode::Receive)).unwrap();
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x95]);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        let packet = mock::block_on(cc1101.receive_async(&mut addr, &mut buf)).unwrap();
+
+        assert_eq!(addr, 0x42);
+        assert_eq!(buf[..2], [1, 2]);
+        assert!(packet.crc_ok);
+        assert_eq!(chip.borrow().register(Config::IOCFG0), 0x3F);
+    }
+
+    #[test]
+    fn test_receive_async_already_received() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_radio_mode(RadioMode::Receive).unwrap();
+        // The packet ended before receive_async was polled, GDO0 has no edges left.
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x95]);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        mock::block_on(cc1101.receive_async(&mut addr, &mut buf)).unwrap();
+
+        assert_eq!(addr, 0x42);
+        assert_eq!(chip.borrow().gdo_waits, 0);
+    }
+
+    #[test]
+    fn test_transmit_async() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let written = mock::block_on(cc1101.transmit_async(&[0x42, 1, 2], &mut mock::Delay));
+        assert_eq!(written.unwrap(), 4);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.sent, [3, 0x42, 1, 2]);
+        assert_eq!(chip.register(Config::IOCFG0), 0x3F);
+        // The simulated chip sends the packet on the first TXBYTES read, before GDO0 is
+        // awaited, so there are no edges left to wait for.
+        assert_eq!(chip.gdo_waits, 0);
+    }
+}
@@ -0,0 +1,61 @@
+/// Bit synchronization, clock recovery, configuration, defaulting to the chip reset values.
+#[derive(Clone, Copy, Debug)]
+pub struct BsConfig {
+    pub(crate) pre_ki: u8,
+    pub(crate) pre_kp: u8,
+    pub(crate) post_ki: u8,
+    pub(crate) post_kp: u8,
+    pub(crate) limit: u8,
+}
+
+impl Default for BsConfig {
+    fn default() -> Self {
+        BsConfig {
+            pre_ki: 1,
+            pre_kp: 2,
+            post_ki: 1,
+            post_kp: 1,
+            limit: 0,
+        }
+    }
+}
+
+impl BsConfig {
+    /// Integral gain before a sync word is detected, KI (0), 2KI (1), 3KI (2) or 4KI (3).
+    pub fn pre_ki(mut self, gain: u8) -> Self {
+        self.pre_ki = gain;
+        self
+    }
+
+    /// Proportional gain before a sync word is detected, KP (0), 2KP (1), 3KP (2) or 4KP (3).
+    pub fn pre_kp(mut self, gain: u8) -> Self {
+        self.pre_kp = gain;
+        self
+    }
+
+    /// Integral gain after a sync word is detected, the pre-sync gain (0) or KI/2 (1).
+    pub fn post_ki(mut self, gain: u8) -> Self {
+        self.post_ki = gain;
+        self
+    }
+
+    /// Proportional gain after a sync word is detected, the pre-sync gain (0) or KP (1).
+    pub fn post_kp(mut self, gain: u8) -> Self {
+        self.post_kp = gain;
+        self
+    }
+
+    /// Data rate offset compensation limit, ±0% (0), ±3.125% (1), ±6.25% (2) or ±12.5% (3).
+    pub fn limit(mut self, limit: u8) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.pre_ki <= 3
+            && self.pre_kp <= 3
+            && self.post_ki <= 1
+            && self.post_kp <= 1
+            && self.limit <= 3
+    }
+}
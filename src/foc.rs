@@ -0,0 +1,50 @@
+/// Frequency offset compensation configuration, defaulting to the chip reset values.
+#[derive(Clone, Copy, Debug)]
+pub struct FocConfig {
+    pub(crate) cs_gate: bool,
+    pub(crate) pre_k: u8,
+    pub(crate) post_k: u8,
+    pub(crate) limit: u8,
+}
+
+impl Default for FocConfig {
+    fn default() -> Self {
+        FocConfig {
+            cs_gate: true,
+            pre_k: 2,
+            post_k: 1,
+            limit: 2,
+        }
+    }
+}
+
+impl FocConfig {
+    /// Freeze frequency offset compensation and clock recovery until carrier sense asserts.
+    pub fn cs_gate(mut self, enabled: bool) -> Self {
+        self.cs_gate = enabled;
+        self
+    }
+
+    /// Loop gain before a sync word is detected, K (0), 2K (1), 3K (2) or 4K (3).
+    pub fn pre_k(mut self, gain: u8) -> Self {
+        self.pre_k = gain;
+        self
+    }
+
+    /// Loop gain after a sync word is detected, the pre-sync gain (0) or K/2 (1).
+    pub fn post_k(mut self, gain: u8) -> Self {
+        self.post_k = gain;
+        self
+    }
+
+    /// Saturation point, compensation disabled (0), ±BW/8 (1), ±BW/4 (2) or ±BW/2 (3) of the
+    /// channel filter bandwidth. Must be 0 for ASK/OOK.
+    pub fn limit(mut self, limit: u8) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.pre_k <= 3 && self.post_k <= 1 && self.limit <= 3
+    }
+}
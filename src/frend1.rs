@@ -0,0 +1,53 @@
+/// Front end RX current configuration, defaulting to the chip reset values.
+///
+/// Each current is a 2-bit setting, 0 to 3, as recommended by SmartRF Studio.
+#[derive(Clone, Copy, Debug)]
+pub struct Frend1Config {
+    pub(crate) lna_current: u8,
+    pub(crate) lna2mix_current: u8,
+    pub(crate) lodiv_buf_current_rx: u8,
+    pub(crate) mix_current: u8,
+}
+
+impl Default for Frend1Config {
+    fn default() -> Self {
+        Frend1Config {
+            lna_current: 1,
+            lna2mix_current: 1,
+            lodiv_buf_current_rx: 1,
+            mix_current: 2,
+        }
+    }
+}
+
+impl Frend1Config {
+    /// Front end LNA PTAT current output.
+    pub fn lna_current(mut self, current: u8) -> Self {
+        self.lna_current = current;
+        self
+    }
+
+    /// Front end PTAT outputs.
+    pub fn lna2mix_current(mut self, current: u8) -> Self {
+        self.lna2mix_current = current;
+        self
+    }
+
+    /// Current in the RX LO buffer, the LO input to the mixer.
+    pub fn lodiv_buf_current_rx(mut self, current: u8) -> Self {
+        self.lodiv_buf_current_rx = current;
+        self
+    }
+
+    /// Current in the mixer.
+    pub fn mix_current(mut self, current: u8) -> Self {
+        self.mix_current = current;
+        self
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        [self.lna_current, self.lna2mix_current, self.lodiv_buf_current_rx, self.mix_current]
+            .iter()
+            .all(|&current| current <= 3)
+    }
+}
@@ -0,0 +1,6 @@
+use crate::lowlevel::FXOSC;
+
+pub fn freqest_to_hz(raw: u8) -> i32 {
+    // According to spec 14.1, in steps of f_osc / 2^14
+    (raw as i8 as i64 * FXOSC as i64 / (1 << 14)) as i32
+}
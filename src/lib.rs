@@ -2,25 +2,133 @@
 
 extern crate embedded_hal as hal;
 
-use hal::blocking::spi::{Transfer, Write};
-use hal::digital::v2::{InputPin, OutputPin};
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Range;
+
+use hal::delay::DelayNs;
+use hal::digital::{InputPin, OutputPin};
+use hal::spi::SpiDevice;
 
 #[macro_use]
 pub mod lowlevel;
+mod agc;
+#[cfg(feature = "async")]
+mod asynch;
+mod bit_sync;
+mod foc;
+mod frend1;
+mod freqest;
+mod lqi;
+#[cfg(test)]
+mod mock;
+mod power;
+mod radio_config;
 mod rssi;
+mod temperature;
 
+use freqest::freqest_to_hz;
 use lowlevel::convert::*;
 use lowlevel::registers::*;
 use lowlevel::types::*;
+use lqi::lqi_to_percent;
+use power::dbm_to_patable;
 use rssi::rssi_to_dbm;
-const MAX_TX: usize = 256;
+use temperature::mv_to_celsius;
+
+pub use agc::AgcConfig;
+pub use bit_sync::BsConfig;
+pub use foc::FocConfig;
+pub use frend1::Frend1Config;
+pub use lowlevel::types::{
+    AgcFilterLength, AskDecisionBoundary, AutoCalibration, CloseInRx, FifoThreshold, GdoCfg,
+    MachineState, MagnTarget,
+};
+pub use lowlevel::NoCs;
+pub use radio_config::RadioConfig;
+/// Polls of MARCSTATE to allow for calibration and PLL lock when entering RX or TX.
+const PLL_LOCK_POLLS: u32 = 1000;
+/// Polls of MARCSTATE and TXBYTES while waiting for a packet to be sent, covering a full TX
+/// FIFO at the lowest data rates with a fast SPI clock.
+const TX_DONE_POLLS: u32 = 250_000;
+/// Interval between polls of the RX FIFO while waiting for an acknowledgement.
+const ACK_POLL_US: u32 = 100;
+/// CC1101 silicon revisions, as reported by the VERSION register.
+const CHIP_VERSIONS: [u8; 2] = [0x04, 0x14];
+/// SPI accesses to allow for the crystal oscillator to start, and the chip to reach IDLE,
+/// after waking up or a reset.
+const CHIP_READY_POLLS: u32 = 1000;
+/// Time CS must be held high before the manual reset sequence pulls it low again.
+const RESET_CS_HIGH_US: u32 = 40;
+/// Time for the RC oscillator calibration to settle after it is enabled.
+const RC_CAL_SETTLE_US: u32 = 2000;
+/// FSCAL1 value after a calibration where the PLL failed to lock.
+const FSCAL1_UNLOCKED: u8 = 0x3F;
+/// PTEST value that makes the on-chip temperature sensor available in the IDLE state.
+const PTEST_TEMP_SENSOR: u8 = 0xBF;
+/// Time for the RSSI to become valid after entering RX, covering the slowest data rates and
+/// narrowest channel filters.
+pub const RSSI_SETTLE_US: u32 = 1000;
+/// Broadcast address accepted by both `AddressFilter::DeviceLowBroadcast` and
+/// `AddressFilter::DeviceHighLowBroadcast`.
+pub const BROADCAST_ADDR: u8 = 0x00;
+/// Size of the RX and TX FIFOs.
+const FIFO_SIZE: usize = 64;
+/// Largest packet `transmit` can send, including any address byte, as the whole packet must
+/// fit the TX FIFO: `MAX_TX` bytes with `PacketLength::Fixed`, and `MAX_TX - 1` with
+/// `PacketLength::Variable`, which also needs room for the length byte. Longer packets can be
+/// sent with `transmit_stream`.
+pub const MAX_TX: usize = FIFO_SIZE;
 /// CC1101 errors.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<SpiE, GpioE> {
     /// The RX FIFO buffer overflowed, too small buffer for configured packet length.
     RxOverflow,
     /// Corrupt packet received with invalid CRC.
     CrcMismatch,
+    /// Requested output power has no PATABLE setting.
+    InvalidPower,
+    /// Requested frequency is outside of the supported bands.
+    FrequencyOutOfRange,
+    /// Requested IF frequency is outside of the representable range.
+    IfFrequencyOutOfRange,
+    /// Requested frequency offset is outside of the representable range.
+    FreqOffsetOutOfRange,
+    /// Requested channel spacing is outside of the representable range.
+    ChannelSpacingOutOfRange,
+    /// The radio did not reach the expected state in time.
+    Timeout,
+    /// The frequency synthesizer failed to lock, such as for an unsupported frequency.
+    PllUnlocked,
+    /// Payload length does not fit the packet length, such as an empty stream.
+    InvalidPacketLength,
+    /// Payload length does not match the configured packet length, or the FIFO.
+    InvalidLength {
+        /// Configured packet length, limited to what fits the FIFO.
+        expected: PacketLength,
+        /// Length of the payload, including any address byte.
+        actual: usize,
+    },
+    /// The TX FIFO ran empty before the whole packet was sent.
+    TxUnderflow,
+    /// Carrier sensed on the channel, so the packet was not sent.
+    ChannelBusy,
+    /// No acknowledgement was received for any of the transmissions of a packet.
+    NoAck,
+    /// Requested setting is incompatible with the rest of the configuration, such as
+    /// Manchester encoding with 4-FSK.
+    InvalidConfig,
+    /// PARTNUM and VERSION do not identify a CC1101, such as when the radio is not powered, or
+    /// mis-wired.
+    UnsupportedChip {
+        /// Value of the PARTNUM register, 0x00 for the CC1101.
+        partnum: u8,
+        /// Value of the VERSION register.
+        version: u8,
+    },
+    /// MARCSTATE reported an undocumented machine state.
+    UnknownMachineState(u8),
     /// Platform-dependent SPI-errors, such as IO errors.
     Spi(SpiE),
     /// Platform-dependent GPIO-errors, such as IO errors.
@@ -36,24 +144,279 @@ impl<SpiE, GpioE> From<lowlevel::Error<SpiE, GpioE>> for Error<SpiE, GpioE> {
     }
 }
 
+impl<SpiE: fmt::Display, GpioE: fmt::Display> fmt::Display for Error<SpiE, GpioE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::RxOverflow => f.write_str("RX FIFO overflow"),
+            Error::CrcMismatch => f.write_str("CRC mismatch"),
+            Error::InvalidPower => f.write_str("invalid output power"),
+            Error::FrequencyOutOfRange => f.write_str("frequency out of range"),
+            Error::IfFrequencyOutOfRange => f.write_str("IF frequency out of range"),
+            Error::FreqOffsetOutOfRange => f.write_str("frequency offset out of range"),
+            Error::ChannelSpacingOutOfRange => f.write_str("channel spacing out of range"),
+            Error::Timeout => f.write_str("timeout"),
+            Error::PllUnlocked => f.write_str("PLL unlocked"),
+            Error::InvalidPacketLength => f.write_str("invalid packet length"),
+            Error::InvalidLength {
+                expected,
+                actual,
+            } => {
+                write!(f, "invalid length {}, expected {:?}", actual, expected)
+            }
+            Error::TxUnderflow => f.write_str("TX FIFO underflow"),
+            Error::ChannelBusy => f.write_str("channel busy"),
+            Error::NoAck => f.write_str("no acknowledgement"),
+            Error::InvalidConfig => f.write_str("invalid configuration"),
+            Error::UnsupportedChip {
+                partnum,
+                version,
+            } => write!(f, "unsupported chip, PARTNUM {:#04x} VERSION {:#04x}", partnum, version),
+            Error::UnknownMachineState(state) => write!(f, "unknown machine state {:#04x}", state),
+            Error::Spi(e) => write!(f, "SPI error: {}", e),
+            Error::Gpio(e) => write!(f, "GPIO error: {}", e),
+        }
+    }
+}
+
 /// High level API for interacting with the CC1101 radio chip.
-pub struct Cc1101<SPI, CS, GDO2>(lowlevel::Cc1101<SPI, CS, GDO2>);
+pub struct Cc1101<SPI, CS, GDO0, GDO2>(lowlevel::Cc1101<SPI, CS, GDO0, GDO2>, State);
+
+/// Driver state not held in the chip registers.
+#[derive(Default)]
+struct State {
+    packet_info: Option<PacketInfo>,
+    band: Option<Band>,
+    /// Length byte of a packet `receive_all` took from the RX FIFO before the rest arrived.
+    rx_len: Option<u8>,
+}
+
+impl<SPI, CS, GDO0, GDO2> Cc1101<SPI, CS, GDO0, GDO2> {
+    /// Destroy the driver, returning the SPI device and pins, for example to reuse the bus once
+    /// the radio is powered down. The radio is left in its current state.
+    pub fn release(self) -> (SPI, CS, GDO0, GDO2) {
+        self.0.release()
+    }
+
+    /// The low-level driver, for raw register access where the high-level API lacks a feature.
+    ///
+    /// This bypasses the high-level invariants: nothing is validated, and cached state such as
+    /// `last_packet_info` is not updated. Leave the radio in a state the high-level API
+    /// expects, such as IDLE, before using it again.
+    pub fn lowlevel(&mut self) -> &mut lowlevel::Cc1101<SPI, CS, GDO0, GDO2> {
+        &mut self.0
+    }
+}
+
+impl<SPI, GDO0, GDO2, SpiE, GpioE> Cc1101<SPI, NoCs<GpioE>, GDO0, GDO2>
+where
+    SPI: SpiDevice<u8, Error = SpiE>,
+    GDO0: InputPin<Error = GpioE>,
+    GDO2: InputPin<Error = GpioE>,
+    GpioE: hal::digital::Error,
+{
+    /// Create a driver on an `SpiDevice` that manages CS itself, such as one sharing its bus
+    /// with other devices.
+    pub fn new_spidevice(spi: SPI, gdo0: GDO0, gdo2: GDO2) -> Result<Self, Error<SpiE, GpioE>> {
+        Ok(Cc1101(lowlevel::Cc1101::new(spi, NoCs::new(), gdo0, gdo2)?, State::default()))
+    }
+}
 
-impl<SPI, CS, GDO2, SpiE, GpioE> Cc1101<SPI, CS, GDO2>
+impl<SPI, CS, GDO0, GDO2, SpiE, GpioE> Cc1101<SPI, CS, GDO0, GDO2>
 where
-    SPI: Transfer<u8, Error = SpiE> + Write<u8, Error = SpiE>,
+    SPI: SpiDevice<u8, Error = SpiE>,
     CS: OutputPin<Error = GpioE>,
+    GDO0: InputPin<Error = GpioE>,
     GDO2: InputPin<Error = GpioE>,
 {
-    pub fn new(spi: SPI, cs: CS, gdo2: GDO2) -> Result<Self, Error<SpiE, GpioE>> {
-        Ok(Cc1101(lowlevel::Cc1101::new(spi, cs, gdo2)?))
+    /// Create a driver toggling a separate `cs` pin around every transaction on `spi`.
+    ///
+    /// A bare `SpiBus` can be wrapped in `embedded_hal_bus::spi::ExclusiveDevice` together with
+    /// its CS pin, and passed to `new_spidevice` instead.
+    pub fn new(spi: SPI, cs: CS, gdo0: GDO0, gdo2: GDO2) -> Result<Self, Error<SpiE, GpioE>> {
+        Ok(Cc1101(lowlevel::Cc1101::new(spi, cs, gdo0, gdo2)?, State::default()))
+    }
+
+    /// Create a driver as `new` does, and check that it talks to a CC1101, returning
+    /// `Error::UnsupportedChip` otherwise.
+    ///
+    /// Catches wiring and power problems at initialization, at the cost of reading the PARTNUM
+    /// and VERSION registers.
+    pub fn new_checked(
+        spi: SPI,
+        cs: CS,
+        gdo0: GDO0,
+        gdo2: GDO2,
+    ) -> Result<Self, Error<SpiE, GpioE>> {
+        let mut cc1101 = Self::new(spi, cs, gdo0, gdo2)?;
+        let (partnum, version) = cc1101.get_hw_info()?;
+        if partnum != 0x00 || !CHIP_VERSIONS.contains(&version) {
+            return Err(Error::UnsupportedChip {
+                partnum,
+                version,
+            });
+        }
+        Ok(cc1101)
+    }
+
+    /// Whether the GDO0 pin is high, see `set_gdo0_config` for its function.
+    pub fn gdo0_is_high(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        self.0.gdo0.is_high().map_err(Error::Gpio)
+    }
+
+    /// Whether the GDO0 pin is low, see `set_gdo0_config` for its function.
+    pub fn gdo0_is_low(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        self.0.gdo0.is_low().map_err(Error::Gpio)
+    }
+
+    /// Whether the GDO2 pin is high, see `set_gdo2_config` for its function.
+    pub fn gdo2_is_high(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        self.0.gdo2.is_high().map_err(Error::Gpio)
+    }
+
+    /// Whether the GDO2 pin is low, see `set_gdo2_config` for its function.
+    pub fn gdo2_is_low(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        self.0.gdo2.is_low().map_err(Error::Gpio)
+    }
+
+    /// Read all configuration registers, IOCFG2 (0x00) to TEST0 (0x2E), in one burst.
+    pub fn dump_config(&mut self, out: &mut [u8; 47]) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.read_burst(Config::IOCFG2, out)?;
+        Ok(())
+    }
+
+    /// Write all configuration registers, IOCFG2 (0x00) to TEST0 (0x2E), in one burst, such as
+    /// a register export from SmartRF Studio. The radio should be in IDLE.
+    pub fn load_config(&mut self, cfg: &[u8; 47]) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.write_burst(Config::IOCFG2, cfg)?;
+        Ok(())
+    }
+
+    /// Write the TEST2, TEST1 and TEST0 registers, whose values are given by SmartRF Studio
+    /// for each configuration. The radio should be in IDLE.
+    pub fn set_test_registers(
+        &mut self,
+        test0: u8,
+        test1: u8,
+        test2: u8,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.write_burst(Config::TEST2, &[test2, test1, test0])?;
+        Ok(())
     }
 
+    /// Read a register by its raw address, for registers not covered by the typed API.
+    ///
+    /// No checks are made, the caller is on their own regarding what the value means.
+    pub fn read_register_raw(&mut self, addr: u8) -> Result<u8, Error<SpiE, GpioE>> {
+        Ok(self.0.read_register_raw(addr)?)
+    }
+
+    /// Write a register by its raw address, for registers not covered by the typed API.
+    ///
+    /// The caller is on their own to keep the radio in a consistent state. Only writes to the
+    /// command strobe addresses (0x30-0x3D) are caught, by a debug assertion.
+    pub fn write_register_raw(&mut self, addr: u8, value: u8) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.write_register_raw(addr, value)?;
+        Ok(())
+    }
+
+    /// Chip status returned at the start of the most recent SPI access, if any.
+    pub fn last_status(&self) -> Option<ChipStatus> {
+        self.0.last_status().map(ChipStatus::from)
+    }
+
+    /// Set the base carrier frequency, which the channel number is added to.
+    ///
+    /// The effective carrier frequency is `base + channel * channel_spacing`, see `set_channel`.
+    /// Supported bands are 300-348 MHz, 387-464 MHz and 779-928 MHz.
     pub fn set_frequency(&mut self, hz: u64) -> Result<(), Error<SpiE, GpioE>> {
+        if !FREQUENCY_BANDS.iter().any(|band| band.contains(&hz)) {
+            return Err(Error::FrequencyOutOfRange);
+        }
         let (freq0, freq1, freq2) = from_frequency(hz);
         self.0.write_register(Config::FREQ0, freq0)?;
         self.0.write_register(Config::FREQ1, freq1)?;
         self.0.write_register(Config::FREQ2, freq2)?;
+        self.1.band = Some(Band::from_frequency(hz));
+        Ok(())
+    }
+
+    /// Override the band inferred from the carrier frequency, which band dependent settings
+    /// such as `set_tx_power_dbm` use, until the next `set_frequency`.
+    pub fn set_band(&mut self, band: Band) {
+        self.1.band = Some(band);
+    }
+
+    /// The band set by `set_band`, or otherwise inferred from the carrier frequency.
+    pub fn current_band(&mut self) -> Result<Band, Error<SpiE, GpioE>> {
+        match self.1.band {
+            Some(band) => Ok(band),
+            None => Ok(Band::from_frequency(self.get_frequency()?)),
+        }
+    }
+
+    /// The base carrier frequency, as programmed, which may be up to ~397 Hz below the
+    /// frequency requested with `set_frequency`.
+    pub fn get_frequency(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        let freq0 = self.0.read_register(Config::FREQ0)?;
+        let freq1 = self.0.read_register(Config::FREQ1)?;
+        let freq2 = self.0.read_register(Config::FREQ2)?;
+        Ok(to_frequency(freq0, freq1, freq2))
+    }
+
+    /// Select the channel, offsetting the carrier from the base set by `set_frequency` by
+    /// `channel * channel_spacing`.
+    pub fn set_channel(&mut self, channel: u8) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.write_register(Config::CHANNR, CHANNR::default().chan(channel).bits())?;
+        Ok(())
+    }
+
+    /// The currently selected channel.
+    pub fn get_channel(&mut self) -> Result<u8, Error<SpiE, GpioE>> {
+        Ok(CHANNR(self.0.read_register(Config::CHANNR)?).chan())
+    }
+
+    /// Set the channel spacing, the distance between the carriers of two adjacent channels.
+    ///
+    /// Supported range is 25.4 kHz to 405.5 kHz.
+    pub fn set_channel_spacing(&mut self, hz: u64) -> Result<(), Error<SpiE, GpioE>> {
+        if !(CHANSPC_MIN..=CHANSPC_MAX).contains(&hz) {
+            return Err(Error::ChannelSpacingOutOfRange);
+        }
+        let (mantissa, exponent) = from_chanspc(hz);
+        self.0
+            .modify_register(Config::MDMCFG1, |r| MDMCFG1(r).modify().chanspc_e(exponent).bits())?;
+        self.0.write_register(Config::MDMCFG0, MDMCFG0::default().chanspc_m(mantissa).bits())?;
+        Ok(())
+    }
+
+    /// Set the output power, in dBm, for the band of the configured carrier frequency, see
+    /// `current_band`.
+    ///
+    /// Supported levels are -30, -20, -15, -10, 0, 5, 7 and 10 dBm.
+    pub fn set_tx_power_dbm(&mut self, dbm: i8) -> Result<(), Error<SpiE, GpioE>> {
+        let band = self.current_band()?;
+
+        let mut patable = [0u8; 8];
+        self.0.read_patable(&mut patable)?;
+        patable[0] = dbm_to_patable(band, dbm).ok_or(Error::InvalidPower)?;
+        self.0.write_patable(&patable)?;
+        Ok(())
+    }
+
+    /// Select the PATABLE entry, 0 to 7, the PA ramps up to, see
+    /// `lowlevel::Cc1101::write_patable`.
+    ///
+    /// The PA ramps through PATABLE entries 0 to `index` at the start of a burst, and back down
+    /// at the end. With ASK/OOK, entry 0 is the power of a 0 and entry `index` that of a 1, so
+    /// `index` must be 1 or more. With the FSK modulations entry `index` is the output power,
+    /// which `set_tx_power_dbm` only sets for an `index` of 0.
+    ///
+    /// Returns `Error::InvalidConfig` if `index` is above 7.
+    pub fn set_pa_power_index(&mut self, index: u8) -> Result<(), Error<SpiE, GpioE>> {
+        if index > 7 {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.modify_register(Config::FREND0, |r| FREND0(r).modify().pa_power(index).bits())?;
         Ok(())
     }
 
@@ -66,7 +429,13 @@ where
         Ok(())
     }
 
+    /// Set the data rate, in baud.
+    ///
+    /// With 4-FSK modulation the data rate is limited to 300 kBaud.
     pub fn set_data_rate(&mut self, baud: u64) -> Result<(), Error<SpiE, GpioE>> {
+        if baud > FSK4_DATA_RATE_MAX && self.is_4fsk()? {
+            return Err(Error::InvalidConfig);
+        }
         let (mantissa, exponent) = from_drate(baud);
         self.0
             .modify_register(Config::MDMCFG4, |r| MDMCFG4(r).modify().drate_e(exponent).bits())?;
@@ -74,6 +443,10 @@ where
         Ok(())
     }
 
+    /// Set the receiver channel filter bandwidth, in Hz, as the nearest setting, see `get_chanbw`.
+    ///
+    /// It should be at least `lowlevel::convert::min_channel_bandwidth` of the data rate and
+    /// deviation, plus the frequency error of both ends.
     pub fn set_chanbw(&mut self, bandwidth: u64) -> Result<(), Error<SpiE, GpioE>> {
         let (mantissa, exponent) = from_chanbw(bandwidth);
         self.0.modify_register(Config::MDMCFG4, |r| {
@@ -82,6 +455,63 @@ where
         Ok(())
     }
 
+    /// The frequency deviation, in Hz, as programmed, which may differ from the deviation
+    /// requested with `set_deviation` due to quantization.
+    pub fn get_deviation(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        let deviatn = DEVIATN(self.0.read_register(Config::DEVIATN)?);
+        Ok(to_deviation(deviatn.deviation_m(), deviatn.deviation_e()))
+    }
+
+    /// The data rate, in baud, as programmed, which may differ from the data rate requested
+    /// with `set_data_rate` due to quantization.
+    pub fn get_data_rate(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        let mdmcfg4 = MDMCFG4(self.0.read_register(Config::MDMCFG4)?);
+        let mdmcfg3 = MDMCFG3(self.0.read_register(Config::MDMCFG3)?);
+        Ok(to_drate(mdmcfg3.drate_m(), mdmcfg4.drate_e()))
+    }
+
+    /// The channel filter bandwidth, in Hz, as programmed, which may differ from the bandwidth
+    /// requested with `set_chanbw` due to quantization.
+    pub fn get_chanbw(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        let mdmcfg4 = MDMCFG4(self.0.read_register(Config::MDMCFG4)?);
+        Ok(to_chanbw(mdmcfg4.chanbw_m(), mdmcfg4.chanbw_e()))
+    }
+
+    /// Set the intermediate frequency used in RX, in steps of f_osc / 2^10, ~25.4 kHz.
+    ///
+    /// Supported range is 0 Hz to 787.1 kHz. A wider channel filter needs a higher IF.
+    pub fn set_if_frequency(&mut self, hz: u64) -> Result<(), Error<SpiE, GpioE>> {
+        if hz > IF_FREQUENCY_MAX {
+            return Err(Error::IfFrequencyOutOfRange);
+        }
+        let freq_if = from_if_frequency(hz);
+        self.0.modify_register(Config::FSCTRL1, |r| FSCTRL1(r).modify().freq_if(freq_if).bits())?;
+        Ok(())
+    }
+
+    /// The intermediate frequency used in RX, in Hz, as programmed.
+    pub fn get_if_frequency(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        Ok(to_if_frequency(FSCTRL1(self.0.read_register(Config::FSCTRL1)?).freq_if()))
+    }
+
+    /// Set the offset added to the base frequency by the synthesizer, such as to compensate
+    /// for a known crystal error, in steps of f_osc / 2^14, ~1587 Hz.
+    ///
+    /// Supported range is -203.1 kHz to 201.5 kHz.
+    pub fn set_freq_offset_correction(&mut self, hz: i32) -> Result<(), Error<SpiE, GpioE>> {
+        if !(FREQOFF_MIN..=FREQOFF_MAX).contains(&hz) {
+            return Err(Error::FreqOffsetOutOfRange);
+        }
+        let freqoff = from_freq_offset(hz);
+        self.0.write_register(Config::FSCTRL0, FSCTRL0::default().freqoff(freqoff).bits())?;
+        Ok(())
+    }
+
+    /// The offset added to the base frequency by the synthesizer, in Hz, as programmed.
+    pub fn get_freq_offset_correction(&mut self) -> Result<i32, Error<SpiE, GpioE>> {
+        Ok(to_freq_offset(FSCTRL0(self.0.read_register(Config::FSCTRL0)?).freqoff()))
+    }
+
     pub fn get_hw_info(&mut self) -> Result<(u8, u8), Error<SpiE, GpioE>> {
         let partnum = self.0.read_register(Status::PARTNUM)?;
         let version = self.0.read_register(Status::VERSION)?;
@@ -93,247 +523,2031 @@ where
         Ok(rssi_to_dbm(self.0.read_register(Status::RSSI)?))
     }
 
+    /// Enter RX, and measure the signal strength of the channel, in dBm, once the RSSI has
+    /// settled.
+    ///
+    /// The RSSI is only valid some time after entering RX, `settle` is used to wait
+    /// `RSSI_SETTLE_US` for it. The radio is left in RX.
+    pub fn measure_channel_rssi<D: DelayNs>(
+        &mut self,
+        settle: &mut D,
+    ) -> Result<i16, Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Receive)?;
+        settle.delay_us(RSSI_SETTLE_US);
+        self.get_rssi_dbm()
+    }
+
+    /// Measure the signal strength, in dBm, of each of `channels` into `out`, such as to pick a
+    /// clear channel.
+    ///
+    /// Each channel is measured as by `measure_channel_rssi`, with `dwell` used to wait
+    /// `RSSI_SETTLE_US` after each channel change for the RSSI to settle, before returning to
+    /// IDLE. `out` should be as long as `channels`, only as many channels as fit `out` are
+    /// scanned. Returns the number of channels scanned. The selected channel is restored
+    /// afterwards.
+    pub fn scan_channels<D: DelayNs>(
+        &mut self,
+        channels: Range<u8>,
+        dwell: &mut D,
+        out: &mut [i16],
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        let selected = self.get_channel()?;
+        let mut scanned = 0;
+        for (channel, rssi) in channels.zip(out.iter_mut()) {
+            self.set_channel(channel)?;
+            *rssi = self.measure_channel_rssi(dwell)?;
+            self.set_radio_mode(RadioMode::Idle)?;
+            scanned += 1;
+        }
+        self.set_channel(selected)?;
+        Ok(scanned)
+    }
+
+    /// Estimated frequency offset, in Hz, of the received carrier relative to the synthesizer.
+    ///
+    /// The estimate is in steps of f_osc / 2^14, independent of the channel filter bandwidth,
+    /// and only valid while receiving.
+    pub fn get_freq_offset(&mut self) -> Result<i32, Error<SpiE, GpioE>> {
+        Ok(freqest_to_hz(self.0.read_register(Status::FREQEST)?))
+    }
+
     /// The Link Quality Indicator metric of the current quality of the received signal.
     pub fn get_lqi(&mut self) -> Result<u8, Error<SpiE, GpioE>> {
         let lqi = self.0.read_register(Status::LQI)?;
         Ok(lqi & !(1u8 << 7))
     }
 
-    /// Configure the sync word to use, and at what level it should be verified.
-    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) -> Result<(), Error<SpiE, GpioE>> {
-        let reset: u16 = (SYNC1::default().bits() as u16) << 8 | (SYNC0::default().bits() as u16);
+    /// The Link Quality Indicator as a percentage, 100 being the best quality.
+    ///
+    /// The 7-bit LQI, where lower is better, is inverted and scaled, so 0 maps to 100% and
+    /// 127 to 0%. Like `get_lqi`, it is only valid right after a packet has been received.
+    pub fn get_link_quality_percent(&mut self) -> Result<u8, Error<SpiE, GpioE>> {
+        Ok(lqi_to_percent(self.get_lqi()?))
+    }
 
-        let (mode, word) = match sync_mode {
-            SyncMode::Disabled => (SyncCheck::DISABLED, reset),
-            SyncMode::MatchPartial(word) => (SyncCheck::CHECK_15_16, word),
-            SyncMode::MatchPartialRepeated(word) => (SyncCheck::CHECK_30_32, word),
-            SyncMode::MatchFull(word) => (SyncCheck::CHECK_16_16, word),
-        };
-        self.0.modify_register(Config::MDMCFG2, |r| {
-            MDMCFG2(r).modify().sync_mode(mode.value()).bits()
-        })?;
-        self.0.write_register(Config::SYNC1, ((word >> 8) & 0xff) as u8)?;
-        self.0.write_register(Config::SYNC0, (word & 0xff) as u8)?;
-        Ok(())
+    /// Measure the die temperature, in °C, using the on-chip analog temperature sensor.
+    ///
+    /// The sensor voltage is routed to the GDO0 pin while the radio is in IDLE, and
+    /// `sample_mv` must return the voltage on GDO0 in millivolts, as measured by an ADC.
+    /// The conversion uses the typical 747 mV at 0 °C and 2.47 mV/°C slope, so expect a few
+    /// degrees of offset unless calibrated. GDO0 configuration is restored afterwards.
+    pub fn read_temperature<F>(&mut self, sample_mv: F) -> Result<i16, Error<SpiE, GpioE>>
+    where
+        F: FnOnce() -> u16,
+    {
+        self.set_radio_mode(RadioMode::Idle)?;
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        self.0.write_register(Config::IOCFG0, IOCFG0(0).modify().temp_sensor_enable(1).bits())?;
+        self.0.write_register(Config::PTEST, PTEST_TEMP_SENSOR)?;
+
+        let mv = sample_mv();
+
+        self.0.write_register(Config::PTEST, PTEST::default().bits())?;
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        Ok(mv_to_celsius(mv))
     }
 
-    /// Configure signal modulation.
-    pub fn set_modulation(&mut self, format: Modulation) -> Result<(), Error<SpiE, GpioE>> {
-        use lowlevel::types::ModFormat as MF;
-
-        let value = match format {
-            Modulation::BinaryFrequencyShiftKeying => MF::MOD_2FSK,
-            Modulation::GaussianFrequencyShiftKeying => MF::MOD_GFSK,
-            Modulation::OnOffKeying => MF::MOD_ASK_OOK,
-            Modulation::FourFrequencyShiftKeying => MF::MOD_4FSK,
-            Modulation::MinimumShiftKeying => MF::MOD_MSK,
-        };
-        self.0.modify_register(Config::MDMCFG2, |r| {
-            MDMCFG2(r).modify().mod_format(value.value()).bits()
-        })?;
+    /// Configure the frequency offset compensation loop.
+    ///
+    /// Returns `Error::InvalidConfig` if a setting does not fit its field.
+    pub fn set_foc_config(&mut self, cfg: FocConfig) -> Result<(), Error<SpiE, GpioE>> {
+        if !cfg.is_valid() {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.write_register(
+            Config::FOCCFG,
+            FOCCFG::default()
+                .foc_bs_cs_gate(cfg.cs_gate as u8)
+                .foc_pre_k(cfg.pre_k)
+                .foc_post_k(cfg.post_k)
+                .foc_limit(cfg.limit)
+                .bits(),
+        )?;
         Ok(())
     }
 
-    /// Configure device address, and address filtering.
-    pub fn set_address_filter(&mut self, filter: AddressFilter) -> Result<(), Error<SpiE, GpioE>> {
-        use lowlevel::types::AddressCheck as AC;
+    /// Configure the bit synchronization, clock recovery, loop.
+    ///
+    /// Returns `Error::InvalidConfig` if a setting does not fit its field.
+    pub fn set_bit_sync_config(&mut self, cfg: BsConfig) -> Result<(), Error<SpiE, GpioE>> {
+        if !cfg.is_valid() {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.write_register(
+            Config::BSCFG,
+            BSCFG::default()
+                .bs_pre_ki(cfg.pre_ki)
+                .bs_pre_kp(cfg.pre_kp)
+                .bs_post_ki(cfg.post_ki)
+                .bs_post_kp(cfg.post_kp)
+                .bs_limit(cfg.limit)
+                .bits(),
+        )?;
+        Ok(())
+    }
 
-        let (mode, addr) = match filter {
-            AddressFilter::Disabled => (AC::DISABLED, ADDR::default().bits()),
-            AddressFilter::Device(addr) => (AC::SELF, addr),
-            AddressFilter::DeviceLowBroadcast(addr) => (AC::SELF_LOW_BROADCAST, addr),
-            AddressFilter::DeviceHighLowBroadcast(addr) => (AC::SELF_HIGH_LOW_BROADCAST, addr),
-        };
-        self.0.modify_register(Config::PKTCTRL1, |r| {
-            PKTCTRL1(r).modify().adr_chk(mode.value()).bits()
-        })?;
-        self.0.write_register(Config::ADDR, addr)?;
+    /// Configure the Automatic Gain Control.
+    ///
+    /// Returns `Error::InvalidConfig` if a setting does not fit its field.
+    pub fn set_agc(&mut self, cfg: AgcConfig) -> Result<(), Error<SpiE, GpioE>> {
+        if !cfg.is_valid() {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.write_register(
+            Config::AGCCTRL2,
+            AGCCTRL2::default()
+                .max_dvga_gain(cfg.max_dvga_gain)
+                .max_lna_gain(cfg.max_lna_gain)
+                .magn_target(cfg.magn_target)
+                .bits(),
+        )?;
+        self.0.write_register(
+            Config::AGCCTRL1,
+            AGCCTRL1::default()
+                .agc_lna_priority(cfg.lna_priority as u8)
+                .carrier_sense_rel_thr(cfg.carrier_sense_rel_thr)
+                .carrier_sense_abs_thr(cfg.carrier_sense_abs_thr as u8 & 0x0F)
+                .bits(),
+        )?;
+        self.0.write_register(
+            Config::AGCCTRL0,
+            AGCCTRL0::default()
+                .hyst_level(cfg.hyst_level)
+                .wait_time(cfg.wait_time)
+                .agc_freeze(cfg.freeze)
+                .filter_length(cfg.filter_length)
+                .bits(),
+        )?;
         Ok(())
     }
 
-    /// Configure packet mode, and length.
-    pub fn set_packet_length(&mut self, length: PacketLength) -> Result<(), Error<SpiE, GpioE>> {
-        use lowlevel::types::LengthConfig as LC;
+    /// Configure the Automatic Gain Control for OOK/ASK reception, as the reset values assume
+    /// FSK.
+    ///
+    /// Following the OOK recommendations of DN022: full gain with a 33 dB magnitude target,
+    /// LNA 2 gain decreased first, medium hysteresis and an 8 dB OOK decision boundary, with the
+    /// gain left free to adjust (AGCCTRL0 = 0x91).
+    pub fn configure_ook_rx(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_agc(
+            AgcConfig::default()
+                .max_dvga_gain(0)
+                .max_lna_gain(0)
+                .magn_target(3)
+                .lna_priority(false)
+                .carrier_sense_rel_thr(0)
+                .carrier_sense_abs_thr(0)
+                .hyst_level(2)
+                .wait_time(1)
+                .freeze(0)
+                .filter_length(1),
+        )
+    }
 
-        let (format, pktlen) = match length {
-            PacketLength::Fixed(limit) => (LC::FIXED, limit),
-            PacketLength::Variable(max_limit) => (LC::VARIABLE, max_limit),
-            PacketLength::Infinite => (LC::INFINITE, PKTLEN::default().bits()),
-        };
-        self.0.modify_register(Config::PKTCTRL0, |r| {
-            PKTCTRL0(r).modify().length_config(format.value()).bits()
+    /// Set the number of channel filter samples the AGC averages the amplitude over.
+    ///
+    /// AGCCTRL0.FILTER_LENGTH sets the OOK/ASK decision boundary instead, so this returns
+    /// `Error::InvalidConfig` if ASK/OOK modulation is configured, see
+    /// `set_ask_decision_boundary`.
+    pub fn set_agc_filter_length(
+        &mut self,
+        len: AgcFilterLength,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        if self.is_ask_ook()? {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.modify_register(Config::AGCCTRL0, |r| {
+            AGCCTRL0(r).modify().filter_length(len.value()).bits()
         })?;
-        self.0.write_register(Config::PKTLEN, pktlen)?;
         Ok(())
     }
 
-    /// Set radio in Receive/Transmit/Idle mode.
-    pub fn set_radio_mode(&mut self, radio_mode: RadioMode) -> Result<(), Error<SpiE, GpioE>> {
-        let target = match radio_mode {
-            RadioMode::Receive => {
-                self.set_radio_mode(RadioMode::Idle)?;
-                self.0.write_strobe(Command::SRX)?;
-                MachineState::RX
-            }
-            RadioMode::Transmit => {
-                self.set_radio_mode(RadioMode::Idle)?;
-                self.0.write_strobe(Command::STX)?;
-                MachineState::TX
-            }
-            RadioMode::Idle => {
-                self.0.write_strobe(Command::SIDLE)?;
-                MachineState::IDLE
-            }
-        };
-        self.await_machine_state(target)
+    /// Set the OOK/ASK decision boundary.
+    ///
+    /// AGCCTRL0.FILTER_LENGTH sets the AGC filter length for the other modulations instead, so
+    /// this returns `Error::InvalidConfig` unless ASK/OOK modulation is configured, see
+    /// `set_agc_filter_length`.
+    pub fn set_ask_decision_boundary(
+        &mut self,
+        db: AskDecisionBoundary,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        if !self.is_ask_ook()? {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.modify_register(Config::AGCCTRL0, |r| {
+            AGCCTRL0(r).modify().filter_length(db.value()).bits()
+        })?;
+        Ok(())
     }
 
-    /// Configure some default settings, to be removed in the future.
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    pub fn set_defaults(&mut self) -> Result<(), Error<SpiE, GpioE>> {
-        self.0.write_strobe(Command::SRES)?;
-
-        self.0.write_register(Config::PKTCTRL0, PKTCTRL0::default()
-            .white_data(0).bits()
-        )?;
-
-        self.0.write_register(Config::FSCTRL1, FSCTRL1::default()
-            .freq_if(0x08).bits() // f_if = (f_osc / 2^10) * FREQ_IF
+    /// Configure the front end RX currents, trading sensitivity for current consumption.
+    ///
+    /// Returns `Error::InvalidConfig` if a current does not fit its 2-bit field.
+    pub fn set_frend1(&mut self, cfg: Frend1Config) -> Result<(), Error<SpiE, GpioE>> {
+        if !cfg.is_valid() {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.write_register(
+            Config::FREND1,
+            FREND1::default()
+                .lna_current(cfg.lna_current)
+                .lna2mix_current(cfg.lna2mix_current)
+                .lodiv_buf_current_rx(cfg.lodiv_buf_current_rx)
+                .mix_current(cfg.mix_current)
+                .bits(),
         )?;
+        Ok(())
+    }
 
-        self.0.write_register(Config::MDMCFG2, MDMCFG2::default()
-            .dem_dcfilt_off(1).bits()
-        )?;
+    /// Apply a preset trade-off between RX sensitivity and current consumption.
+    ///
+    /// Touches MDMCFG2.DEM_DCFILT_OFF, see `set_dc_filter`, all of FREND1, see `set_frend1`,
+    /// and all of AGCCTRL2, AGCCTRL1 and AGCCTRL0, which are reset to full gain, see `set_agc`:
+    ///
+    /// - `HighSensitivity`: DC filter enabled, FREND1 0xB6.
+    /// - `Balanced`: DC filter enabled, FREND1 0x56.
+    /// - `LowCurrent`: DC filter disabled, FREND1 0x56.
+    ///
+    /// Returns `Error::InvalidConfig` for `LowCurrent` above 250 kBaud, where the DC filter
+    /// is required.
+    pub fn set_sensitivity_mode(
+        &mut self,
+        mode: SensitivityMode,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let (dc_filter, frend1) = mode.dc_filter_and_frend1();
+        if !dc_filter && self.get_data_rate()? > 250_000 {
+            return Err(Error::InvalidConfig);
+        }
+        self.set_dc_filter(dc_filter)?;
+        self.set_frend1(frend1)?;
+        self.set_agc(AgcConfig::default())
+    }
 
-        self.0.write_register(Config::MCSM0, MCSM0::default()
-            .fs_autocal(AutoCalibration::FROM_IDLE.value()).bits()
-        )?;
+    /// Limit the LNA + LNA 2 gain to `steps` steps, of roughly 2.6 to 3 dB, below maximum, 0
+    /// to 7, trading sensitivity for handling of strong signals.
+    ///
+    /// Returns `Error::InvalidConfig` if `steps` is above 7.
+    pub fn set_max_lna_gain(&mut self, steps: u8) -> Result<(), Error<SpiE, GpioE>> {
+        if steps > 7 {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.modify_register(Config::AGCCTRL2, |r| {
+            AGCCTRL2(r).modify().max_lna_gain(steps).bits()
+        })?;
+        Ok(())
+    }
 
-        self.0.write_register(Config::AGCCTRL2, AGCCTRL2::default()
-            .max_lna_gain(0x04).bits()
-        )?;
+    /// Set the target amplitude from the channel filter, which the AGC adjusts the gain for.
+    pub fn set_magn_target(&mut self, target: MagnTarget) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::AGCCTRL2, |r| {
+            AGCCTRL2(r).modify().magn_target(target.value()).bits()
+        })?;
+        Ok(())
+    }
 
+    /// Configure the sync word to use, and at what level it should be verified.
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) -> Result<(), Error<SpiE, GpioE>> {
+        let (mode, word) = sync_mode.check_and_word();
+        self.0.modify_register(Config::MDMCFG2, |r| {
+            MDMCFG2(r).modify().sync_mode(mode.value()).bits()
+        })?;
+        self.0.write_register(Config::SYNC1, ((word >> 8) & 0xff) as u8)?;
+        self.0.write_register(Config::SYNC0, (word & 0xff) as u8)?;
         Ok(())
     }
 
-    fn await_machine_state(&mut self, target: MachineState) -> Result<(), Error<SpiE, GpioE>> {
-        loop {
-            let marcstate = MARCSTATE(self.0.read_register(Status::MARCSTATE)?);
-            if target.value() == marcstate.marc_state() {
-                break;
-            }
-        }
+    /// Enable or disable Forward Error Correction with interleaving.
+    ///
+    /// FEC is only supported with fixed or variable packet length, not infinite, and needs
+    /// preamble and sync word detection enabled.
+    pub fn set_fec(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::MDMCFG1, |r| {
+            MDMCFG1(r).modify().fec_en(enabled as u8).bits()
+        })?;
         Ok(())
     }
 
-    fn rx_bytes_available(&mut self) -> Result<u8, Error<SpiE, GpioE>> {
-        let mut last = 0;
+    /// Enable or disable data whitening, XOR-ing the payload with a PN9 sequence.
+    ///
+    /// Both ends of the link must agree on whitening.
+    pub fn set_whitening(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::PKTCTRL0, |r| {
+            PKTCTRL0(r).modify().white_data(enabled as u8).bits()
+        })?;
+        Ok(())
+    }
 
-        loop {
-            let rxbytes = RXBYTES(self.0.read_register(Status::RXBYTES)?);
-            if rxbytes.rxfifo_overflow() == 1 {
-                return Err(Error::RxOverflow);
-            }
+    /// Enable or disable CRC calculation in TX and CRC check in RX.
+    ///
+    /// With CRC disabled, `receive` returns packets without verifying them.
+    pub fn set_crc(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::PKTCTRL0, |r| {
+            PKTCTRL0(r).modify().crc_en(enabled as u8).bits()
+        })?;
+        Ok(())
+    }
 
-            let nbytes = rxbytes.num_rxbytes();
-            if nbytes > 0 && nbytes == last {
-                break;
-            }
+    /// Enable or disable automatic flush of the RX FIFO when the CRC check fails.
+    ///
+    /// With autoflush enabled, a corrupt packet is discarded by the chip before it can be read,
+    /// so `receive` keeps waiting for the next packet instead of returning `Error::CrcMismatch`.
+    pub fn set_crc_autoflush(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::PKTCTRL1, |r| {
+            PKTCTRL1(r).modify().crc_autoflush(enabled as u8).bits()
+        })?;
+        Ok(())
+    }
 
-            last = nbytes;
+    /// Set the preamble quality threshold, 0 to 7, which gates sync word detection.
+    ///
+    /// A sync word is only accepted once the preamble quality estimate reaches `4 * pqt`,
+    /// reducing false sync on noise. A `pqt` of 0 disables the preamble quality check.
+    /// Returns `Error::InvalidConfig` if `pqt` is above 7.
+    pub fn set_pqt(&mut self, pqt: u8) -> Result<(), Error<SpiE, GpioE>> {
+        if pqt > 7 {
+            return Err(Error::InvalidConfig);
         }
-        Ok(last)
+        self.0.modify_register(Config::PKTCTRL1, |r| PKTCTRL1(r).modify().pqt(pqt).bits())?;
+        Ok(())
     }
 
-    // Should also be able to configure MCSM1.RXOFF_MODE to declare what state
-    // to enter after fully receiving a packet.
-    // Possible targets: IDLE, FSTON, TX, RX
-    pub fn receive(&mut self, addr: &mut u8, buf: &mut [u8]) -> Result<u8, Error<SpiE, GpioE>> {
-        match self.rx_bytes_available() {
-            Ok(_nbytes) => {
-                let mut length = 0u8;
-                self.0.read_fifo(addr, &mut length, buf)?;
-                let lqi = self.0.read_register(Status::LQI)?;
-                self.await_machine_state(MachineState::IDLE)?;
-                self.0.write_strobe(Command::SFRX)?;
-                if (lqi >> 7) != 1 {
-                    Err(Error::CrcMismatch)
-                } else {
-                    Ok(length)
-                }
-            }
-            Err(err) => {
-                self.0.write_strobe(Command::SFRX)?;
-                Err(err)
-            }
-        }
+    /// Configure the minimum number of preamble bytes to be transmitted.
+    pub fn set_preamble(&mut self, pre: PreambleLength) -> Result<(), Error<SpiE, GpioE>> {
+        let value = pre.num_preamble();
+        self.0.modify_register(Config::MDMCFG1, |r| {
+            MDMCFG1(r).modify().num_preamble(value.value()).bits()
+        })?;
+        Ok(())
     }
 
-    pub fn transmit(&mut self, payload: &[u8], len: u8) -> Result<(), Error<SpiE, GpioE>> {
-        // let ret: u8 = PAYLOAD_TRANSMITTED;
+    /// Enable or disable appending the RSSI and LQI status bytes to received packets.
+    ///
+    /// When enabled, `receive` reports the status bytes received with the packet rather than
+    /// reading the status registers after the fact, which may already describe the next packet.
+    /// The status bytes are read separately, so the receive buffer only needs room for the
+    /// payload.
+    pub fn set_append_status(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::PKTCTRL1, |r| {
+            PKTCTRL1(r).modify().append_status(enabled as u8).bits()
+        })?;
+        Ok(())
+    }
 
-        if len > 0 && len < 62 {
-            self.0.write_register(Config::IOCFG0, 0x09)?;
-            //
-            let mut tx_buffer: [u8; 64] = [0; 64];
-            tx_buffer[0] = len;
-            tx_buffer[1..].copy_from_slice(payload);
-            // // memcpy(tx_buffer + 1, payload, len);
-            // // cc1101_idle_mode();
-            self.set_radio_mode(RadioMode::Idle)?;
-            // // cc1101_write_strobe(SFTX); // Flush TX_FIFO
-            self.0.write_strobe(Command::SFTX)?;
-            // self.set_radio_mode(RadioMode::Idle)?;
-            // funcptr.delay_us(100); /TODO
-            // cc1101_receive_mode();
-            self.set_radio_mode(RadioMode::Receive)?;
-            self.0.write_burst(Command::FIFO, &mut tx_buffer)?;
-            // funcptr.delay_ms(1); // Wait for CCA to be asserted //TODO
-
-            // for i in 0..100_000_000 {}
-            // if (funcptr.gdo0()) { //TODO
-            // Listen before Talk
-            self.0.write_register(Config::IOCFG0, 0x06)?; //TODO ???
-                                                          // cc1101_write_register(IOCFG0, 0x06);
-                                                          // self.0.write_strobe(Command::STX)?; // Sends Data
-
-            self.set_radio_mode(RadioMode::Transmit)?;
-            // // Wait for GDO2 to be set -> sync transmitted
-            let mut waiting_for_sync = true;
-            while waiting_for_sync {
-                if let Ok(gdo2_state) = self.0.gdo2.is_low() {
-                    waiting_for_sync = gdo2_state;
-                }
-            }
-            let mut waiting_for_transmit = true;
-            while waiting_for_transmit {
-                if let Ok(gdo2_state) = self.0.gdo2.is_low() {
-                    waiting_for_transmit = !gdo2_state;
-                }
+    /// Configure signal modulation.
+    ///
+    /// 4-FSK is incompatible with Manchester encoding, and data rates above 300 kBaud.
+    pub fn set_modulation(&mut self, format: Modulation) -> Result<(), Error<SpiE, GpioE>> {
+        if format == Modulation::FourFrequencyShiftKeying {
+            let manchester = MDMCFG2(self.0.read_register(Config::MDMCFG2)?).manchester_en() == 1;
+            if manchester || self.get_data_rate()? > FSK4_DATA_RATE_MAX {
+                return Err(Error::InvalidConfig);
             }
-            self.set_radio_mode(RadioMode::Idle)?;
-            // while (!funcptr.gdo2())
-            // 	;
-            //
-            // // Wait for GDO2 to be cleared -> end of packet
-            // while (funcptr.gdo2())
-            // 	;
-            // } else { //TODO
-            //     cc1101_idle_mode();
-            //     cc1101_write_strobe(SFTX); // Flush TX_FIFO
-            //     funcptr.delay_us(100);
-            //     // ret = NOISE_ON_CHANNEL;
-            // }
-        } else {
-            // ret = PAYLOAD_LEN_OUT_OF_RANGE;
         }
-
+        let value = format.mod_format();
+        self.0.modify_register(Config::MDMCFG2, |r| {
+            MDMCFG2(r).modify().mod_format(value.value()).bits()
+        })?;
         Ok(())
-        // return ret;
     }
-}
+
+    /// Enable or disable Manchester encoding and decoding.
+    ///
+    /// Manchester encoding is not supported with 4-FSK modulation.
+    pub fn set_manchester(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        if enabled && self.is_4fsk()? {
+            return Err(Error::InvalidConfig);
+        }
+        self.0.modify_register(Config::MDMCFG2, |r| {
+            MDMCFG2(r).modify().manchester_en(enabled as u8).bits()
+        })?;
+        Ok(())
+    }
+
+    fn is_ask_ook(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        let mod_format = MDMCFG2(self.0.read_register(Config::MDMCFG2)?).mod_format();
+        Ok(mod_format == ModFormat::MOD_ASK_OOK.value())
+    }
+
+    fn is_4fsk(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        let mod_format = MDMCFG2(self.0.read_register(Config::MDMCFG2)?).mod_format();
+        Ok(mod_format == ModFormat::MOD_4FSK.value())
+    }
+
+    /// Enable or disable the digital DC blocking filter before the demodulator.
+    ///
+    /// The filter improves sensitivity, but disabling it lowers current consumption, and is
+    /// only allowed for data rates up to 250 kBaud.
+    pub fn set_dc_filter(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::MDMCFG2, |r| {
+            MDMCFG2(r).modify().dem_dcfilt_off(!enabled as u8).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure device address, and address filtering.
+    pub fn set_address_filter(&mut self, filter: AddressFilter) -> Result<(), Error<SpiE, GpioE>> {
+        let (mode, addr) = filter.check_and_addr();
+        self.0.modify_register(Config::PKTCTRL1, |r| {
+            PKTCTRL1(r).modify().adr_chk(mode.value()).bits()
+        })?;
+        self.0.write_register(Config::ADDR, addr)?;
+        Ok(())
+    }
+
+    /// Configure packet mode, and length.
+    pub fn set_packet_length(&mut self, length: PacketLength) -> Result<(), Error<SpiE, GpioE>> {
+        let (format, pktlen) = length.config_and_len();
+        self.0.modify_register(Config::PKTCTRL0, |r| {
+            PKTCTRL0(r).modify().length_config(format.value()).bits()
+        })?;
+        self.0.write_register(Config::PKTLEN, pktlen)?;
+        Ok(())
+    }
+
+    /// The configured packet mode, and length.
+    pub fn get_packet_length(&mut self) -> Result<PacketLength, Error<SpiE, GpioE>> {
+        use lowlevel::types::LengthConfig as LC;
+
+        let format = PKTCTRL0(self.0.read_register(Config::PKTCTRL0)?).length_config();
+        let pktlen = PKTLEN(self.0.read_register(Config::PKTLEN)?).packet_length();
+        Ok(if format == LC::FIXED.value() {
+            PacketLength::Fixed(pktlen)
+        } else if format == LC::VARIABLE.value() {
+            PacketLength::Variable(pktlen)
+        } else {
+            PacketLength::Infinite
+        })
+    }
+
+    /// Configure the TX and RX FIFO fill levels at which the FIFO threshold GDO signals assert.
+    ///
+    /// The ADC retention and close-in RX attenuation settings are preserved.
+    pub fn set_fifo_threshold(&mut self, thr: FifoThreshold) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::FIFOTHR, |r| {
+            FIFOTHR(r).modify().fifo_thr(thr.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Attenuate the RX input, to avoid saturating the front end with a transmitter close by.
+    ///
+    /// The FIFO threshold and ADC retention settings are preserved.
+    pub fn set_close_in_rx(&mut self, atten: CloseInRx) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::FIFOTHR, |r| {
+            FIFOTHR(r).modify().close_in_rx(atten.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure the function of the GDO0 pin.
+    pub fn set_gdo0_config(&mut self, cfg: GdoCfg) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::IOCFG0, |r| {
+            IOCFG0(r).modify().temp_sensor_enable(0).gdo0_cfg(cfg.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure the function of the GDO1 pin, which is shared with the SPI SO line.
+    pub fn set_gdo1_config(&mut self, cfg: GdoCfg) -> Result<(), Error<SpiE, GpioE>> {
+        self.0
+            .modify_register(Config::IOCFG1, |r| IOCFG1(r).modify().gdo1_cfg(cfg.value()).bits())?;
+        Ok(())
+    }
+
+    /// Configure the function of the GDO2 pin.
+    pub fn set_gdo2_config(&mut self, cfg: GdoCfg) -> Result<(), Error<SpiE, GpioE>> {
+        self.0
+            .modify_register(Config::IOCFG2, |r| IOCFG2(r).modify().gdo2_cfg(cfg.value()).bits())?;
+        Ok(())
+    }
+
+    /// Invert the GDO0 output, making its signal active low.
+    pub fn set_gdo0_invert(&mut self, inverted: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::IOCFG0, |r| {
+            IOCFG0(r).modify().gdo0_inv(inverted as u8).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Invert the GDO1 output, making its signal active low.
+    pub fn set_gdo1_invert(&mut self, inverted: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::IOCFG1, |r| {
+            IOCFG1(r).modify().gdo1_inv(inverted as u8).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Invert the GDO2 output, making its signal active low.
+    pub fn set_gdo2_invert(&mut self, inverted: bool) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::IOCFG2, |r| {
+            IOCFG2(r).modify().gdo2_inv(inverted as u8).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Output the crystal clock, divided by `divider`, on a GDO pin.
+    pub fn enable_clock_output(
+        &mut self,
+        pin: GdoPin,
+        divider: ClockDivider,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let cfg = match divider {
+            ClockDivider::Div1 => GdoCfg::CLK_XOSC_1,
+            ClockDivider::Div1_5 => GdoCfg::CLK_XOSC_1_5,
+            ClockDivider::Div2 => GdoCfg::CLK_XOSC_2,
+            ClockDivider::Div3 => GdoCfg::CLK_XOSC_3,
+            ClockDivider::Div4 => GdoCfg::CLK_XOSC_4,
+            ClockDivider::Div6 => GdoCfg::CLK_XOSC_6,
+            ClockDivider::Div8 => GdoCfg::CLK_XOSC_8,
+            ClockDivider::Div12 => GdoCfg::CLK_XOSC_12,
+            ClockDivider::Div16 => GdoCfg::CLK_XOSC_16,
+            ClockDivider::Div24 => GdoCfg::CLK_XOSC_24,
+            ClockDivider::Div32 => GdoCfg::CLK_XOSC_32,
+            ClockDivider::Div48 => GdoCfg::CLK_XOSC_48,
+            ClockDivider::Div64 => GdoCfg::CLK_XOSC_64,
+            ClockDivider::Div96 => GdoCfg::CLK_XOSC_96,
+            ClockDivider::Div128 => GdoCfg::CLK_XOSC_128,
+            ClockDivider::Div192 => GdoCfg::CLK_XOSC_192,
+        };
+        match pin {
+            GdoPin::Gdo0 => self.set_gdo0_config(cfg),
+            GdoPin::Gdo1 => self.set_gdo1_config(cfg),
+            GdoPin::Gdo2 => self.set_gdo2_config(cfg),
+        }
+    }
+
+    /// Signal received packets on a GDO pin, for `receive_on_interrupt`.
+    ///
+    /// The pin is configured for SYNC_WORD (0x06), which asserts when the sync word has been
+    /// received, and de-asserts at the end of the packet. Trigger the interrupt on the falling
+    /// edge.
+    pub fn enable_packet_interrupt(&mut self, pin: GdoPin) -> Result<(), Error<SpiE, GpioE>> {
+        match pin {
+            GdoPin::Gdo0 => self.set_gdo0_config(GdoCfg::SYNC_WORD),
+            GdoPin::Gdo1 => self.set_gdo1_config(GdoCfg::SYNC_WORD),
+            GdoPin::Gdo2 => self.set_gdo2_config(GdoCfg::SYNC_WORD),
+        }
+    }
+
+    /// Calibrate the frequency synthesizer, and wait for the calibration to complete.
+    ///
+    /// Leaves the radio in IDLE.
+    pub fn calibrate(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)?;
+        self.0.write_strobe(Command::SCAL)?;
+        self.await_machine_state(MachineState::IDLE)
+    }
+
+    /// Configure which state transitions automatically calibrate the frequency synthesizer.
+    ///
+    /// With `AutoCalibration::DISABLED`, calibrate manually using `calibrate`.
+    pub fn set_autocal_mode(&mut self, mode: AutoCalibration) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.modify_register(Config::MCSM0, |r| {
+            MCSM0(r).modify().fs_autocal(mode.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure Wake-on-Radio, periodically waking from SLEEP to listen for a packet.
+    ///
+    /// The chip wakes every `event0_period_ms`, where `t_event0 = 750 / f_xosc * EVENT0 *
+    /// 2^(5 * WOR_RES)`, and listens for a sync word for about `rx_timeout_ms`. The RX timeout
+    /// is a fraction of the wake period selected by MCSM2.RX_TIME, so it is rounded down to the
+    /// nearest supported fraction. RC oscillator calibration is enabled, see
+    /// `calibrate_rc_osc`.
+    pub fn configure_wor(
+        &mut self,
+        rx_timeout_ms: u32,
+        event0_period_ms: u32,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let (event0, wor_res) = from_wor_event0(event0_period_ms as u64);
+        let rx_time = from_wor_rx_timeout(rx_timeout_ms as u64, event0_period_ms as u64, wor_res);
+
+        self.0.write_register(
+            Config::WOREVT1,
+            WOREVT1::default().event((event0 >> 8) as u8).bits(),
+        )?;
+        self.0.write_register(Config::WOREVT0, WOREVT0::default().event(event0 as u8).bits())?;
+        self.0.modify_register(Config::WORCTRL, |r| {
+            WORCTRL(r).modify().rc_pd(0).rc_cal(1).wor_res(wor_res).bits()
+        })?;
+        self.0.modify_register(Config::MCSM2, |r| MCSM2(r).modify().rx_time(rx_time).bits())?;
+        Ok(())
+    }
+
+    /// Power up and calibrate the low-power RC oscillator timing Wake-on-Radio, waiting
+    /// `RC_CAL_SETTLE_US` using `delay` for the calibration to settle. Leaves the radio in IDLE.
+    ///
+    /// The RC oscillator is calibrated against the crystal while it runs, so redo this before
+    /// `start_wor` after temperature or supply voltage changes, as an uncalibrated oscillator
+    /// makes the wake period inaccurate.
+    pub fn calibrate_rc_osc<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)?;
+        self.0
+            .modify_register(Config::WORCTRL, |r| WORCTRL(r).modify().rc_pd(0).rc_cal(1).bits())?;
+        delay.delay_us(RC_CAL_SETTLE_US);
+        Ok(())
+    }
+
+    /// The Wake-on-Radio timer, counting up in steps of the EVENT0 resolution set by
+    /// `configure_wor`, and restarting at each EVENT0.
+    ///
+    /// The status registers are read one at a time while the timer runs, so WORTIME1 is read
+    /// again, and the read retried, if it changed.
+    pub fn get_wor_time(&mut self) -> Result<u16, Error<SpiE, GpioE>> {
+        let mut wortime1 = self.0.read_register(Status::WORTIME1)?;
+        loop {
+            let wortime0 = self.0.read_register(Status::WORTIME0)?;
+            let again = self.0.read_register(Status::WORTIME1)?;
+            if again == wortime1 {
+                return Ok(u16::from_be_bytes([wortime1, wortime0]));
+            }
+            wortime1 = again;
+        }
+    }
+
+    /// The Wake-on-Radio timer, in ms, converted using the configured WORCTRL.WOR_RES.
+    pub fn get_wor_time_ms(&mut self) -> Result<u64, Error<SpiE, GpioE>> {
+        let time = self.get_wor_time()?;
+        let wor_res = WORCTRL(self.0.read_register(Config::WORCTRL)?).wor_res();
+        Ok(to_wor_event0(time, wor_res))
+    }
+
+    /// Start the automatic Wake-on-Radio RX polling sequence.
+    pub fn start_wor(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)?;
+        self.0.write_strobe(Command::SWORRST)?;
+        self.0.write_strobe(Command::SWOR)?;
+        Ok(())
+    }
+
+    /// Stop Wake-on-Radio polling, leaving the radio in IDLE.
+    pub fn stop_wor(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)
+    }
+
+    /// Set radio in Receive/Transmit/Idle mode.
+    ///
+    /// From `RadioMode::FastReady` the radio enters RX or TX without recalibrating, otherwise
+    /// it passes through IDLE first.
+    pub fn set_radio_mode(&mut self, radio_mode: RadioMode) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode_polls(radio_mode, None)
+    }
+
+    /// Set radio in Receive/Transmit/Idle mode, giving up with `Error::Timeout` if the mode
+    /// isn't reached within `max_polls` reads of the machine state.
+    pub fn set_radio_mode_timeout(
+        &mut self,
+        radio_mode: RadioMode,
+        max_polls: u32,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode_polls(radio_mode, Some(max_polls))
+    }
+
+    /// Enter RX, returning the machine state observed once entered.
+    ///
+    /// Unlike `set_radio_mode`, a state other than RX is returned rather than waited out,
+    /// such as RXFIFO_OVERFLOW, if RX is not reached within `PLL_LOCK_POLLS` polls.
+    pub fn enter_rx(&mut self) -> Result<MachineState, Error<SpiE, GpioE>> {
+        self.enter_state(RadioMode::Receive)
+    }
+
+    /// Enter TX, returning the machine state observed once entered.
+    ///
+    /// Unlike `set_radio_mode`, a state other than TX is returned rather than waited out,
+    /// such as TXFIFO_UNDERFLOW, if TX is not reached within `PLL_LOCK_POLLS` polls.
+    pub fn enter_tx(&mut self) -> Result<MachineState, Error<SpiE, GpioE>> {
+        self.enter_state(RadioMode::Transmit)
+    }
+
+    /// Enter IDLE, returning the machine state observed once entered.
+    ///
+    /// Unlike `set_radio_mode`, a state other than IDLE is returned rather than waited out, if
+    /// IDLE is not reached within `PLL_LOCK_POLLS` polls.
+    pub fn enter_idle(&mut self) -> Result<MachineState, Error<SpiE, GpioE>> {
+        self.enter_state(RadioMode::Idle)
+    }
+
+    fn enter_state(&mut self, radio_mode: RadioMode) -> Result<MachineState, Error<SpiE, GpioE>> {
+        match self.set_radio_mode_polls(radio_mode, Some(PLL_LOCK_POLLS)) {
+            Ok(()) | Err(Error::Timeout) => self.get_machine_state(),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn set_radio_mode_polls(
+        &mut self,
+        radio_mode: RadioMode,
+        max_polls: Option<u32>,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let target = match radio_mode {
+            RadioMode::Receive => {
+                self.leave_for_synthesizer_state(max_polls)?;
+                self.0.write_strobe(Command::SRX)?;
+                MachineState::RX
+            }
+            RadioMode::Transmit => {
+                self.leave_for_synthesizer_state(max_polls)?;
+                self.0.write_strobe(Command::STX)?;
+                MachineState::TX
+            }
+            RadioMode::FastReady => {
+                self.leave_for_synthesizer_state(max_polls)?;
+                self.0.write_strobe(Command::SFSTXON)?;
+                MachineState::FSTXON
+            }
+            RadioMode::Idle => {
+                self.0.write_strobe(Command::SIDLE)?;
+                return self.await_machine_state_timeout(MachineState::IDLE, max_polls);
+            }
+        };
+        self.await_synthesizer_state(target, max_polls)
+    }
+
+    /// Enter IDLE before a state that needs the frequency synthesizer, unless already in
+    /// FSTXON, where the synthesizer is running and calibrated.
+    fn leave_for_synthesizer_state(
+        &mut self,
+        max_polls: Option<u32>,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        if self.get_machine_state()? == MachineState::FSTXON {
+            return Ok(());
+        }
+        self.set_radio_mode_polls(RadioMode::Idle, max_polls)
+    }
+
+    /// Whether the frequency synthesizer is in lock, as last calibrated.
+    pub fn is_pll_locked(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        let fscal1 = FSCAL1(self.0.read_register(Config::FSCAL1)?);
+        Ok(fscal1.fscal1() != FSCAL1_UNLOCKED)
+    }
+
+    /// Wait for a state that needs the frequency synthesizer, failing with
+    /// `Error::PllUnlocked` if it has not locked within `PLL_LOCK_POLLS` polls.
+    fn await_synthesizer_state(
+        &mut self,
+        target: MachineState,
+        max_polls: Option<u32>,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let first = max_polls.map_or(PLL_LOCK_POLLS, |max| max.min(PLL_LOCK_POLLS));
+        match self.await_machine_state_timeout(target, Some(first)) {
+            Err(Error::Timeout) => {
+                if !self.is_pll_locked()? {
+                    return Err(Error::PllUnlocked);
+                }
+                match max_polls {
+                    Some(max) if max <= first => Err(Error::Timeout),
+                    max_polls => {
+                        self.await_machine_state_timeout(target, max_polls.map(|max| max - first))
+                    }
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Stay in RX after a packet has been received, instead of returning to IDLE, for
+    /// continuous reception without recalibrating between packets.
+    ///
+    /// Sets RXOFF_MODE to RX, or IDLE when disabled, see `set_rxoff_mode`. While it is RX,
+    /// receiving a packet leaves the radio in RX and does not flush the RX FIFO, so packets
+    /// arriving in the meantime are kept: `buf` must fit a whole packet, otherwise the FIFO is
+    /// flushed to resynchronize, and packets queuing up faster than they are read overflow the
+    /// FIFO, see `receive_all`.
+    pub fn set_rx_continuous(&mut self, enabled: bool) -> Result<(), Error<SpiE, GpioE>> {
+        let state = if enabled {
+            RadioMode::Receive
+        } else {
+            RadioMode::Idle
+        };
+        self.set_rxoff_mode(state)
+    }
+
+    fn rx_continuous_enabled(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        let mcsm1 = MCSM1(self.0.read_register(Config::MCSM1)?);
+        Ok(mcsm1.rxoff_mode() == lowlevel::types::RxOffMode::RX.value())
+    }
+
+    /// Configure what state to enter after a packet has been received.
+    ///
+    /// `RadioMode::FastReady` selects FSTXON, keeping the synthesizer running for a fast
+    /// turnaround to TX.
+    pub fn set_rxoff_mode(&mut self, state: RadioMode) -> Result<(), Error<SpiE, GpioE>> {
+        use lowlevel::types::RxOffMode as RO;
+
+        let value = match state {
+            RadioMode::Receive => RO::RX,
+            RadioMode::Transmit => RO::TX,
+            RadioMode::Idle => RO::IDLE,
+            RadioMode::FastReady => RO::FSTXON,
+        };
+        self.0.modify_register(Config::MCSM1, |r| {
+            MCSM1(r).modify().rxoff_mode(value.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure what state to enter after a packet has been sent.
+    ///
+    /// `RadioMode::FastReady` selects FSTXON, keeping the synthesizer running for a fast
+    /// turnaround to RX.
+    pub fn set_txoff_mode(&mut self, state: RadioMode) -> Result<(), Error<SpiE, GpioE>> {
+        use lowlevel::types::TxOffMode as TO;
+
+        let value = match state {
+            RadioMode::Receive => TO::RX,
+            RadioMode::Transmit => TO::TX,
+            RadioMode::Idle => TO::IDLE,
+            RadioMode::FastReady => TO::FSTXON,
+        };
+        self.0.modify_register(Config::MCSM1, |r| {
+            MCSM1(r).modify().txoff_mode(value.value()).bits()
+        })?;
+        Ok(())
+    }
+
+    /// Configure when the channel is considered clear, which gates entering TX from RX.
+    pub fn set_cca_mode(&mut self, mode: CcaMode) -> Result<(), Error<SpiE, GpioE>> {
+        use lowlevel::types::CcaCheck as CC;
+
+        let value = match mode {
+            CcaMode::Always => CC::ALWAYS,
+            CcaMode::RssiBelowThreshold => CC::RSSI_BELOW_THR,
+            CcaMode::UnlessReceiving => CC::UNLESS_RECEIVING,
+            CcaMode::RssiBelowThresholdUnlessReceiving => CC::RSSI_BELOW_THR_UNLESS_RECEIVING,
+        };
+        self.0
+            .modify_register(Config::MCSM1, |r| MCSM1(r).modify().cca_mode(value.value()).bits())?;
+        Ok(())
+    }
+
+    /// Whether the RSSI is above the carrier sense threshold configured with `set_agc`.
+    ///
+    /// Only valid in RX, once the RSSI has settled.
+    pub fn carrier_sense(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        Ok(PKTSTATUS(self.0.read_register(Status::PKTSTATUS)?).cs() == 1)
+    }
+
+    /// Real-time packet and channel status flags.
+    pub fn get_pkt_status(&mut self) -> Result<PktStatus, Error<SpiE, GpioE>> {
+        Ok(PktStatus::from(self.0.read_register(Status::PKTSTATUS)?))
+    }
+
+    /// Read all status registers, PARTNUM (0x30) to RXBYTES (0x3B).
+    ///
+    /// The chip has no burst access to the status registers, so each is read on its own, and
+    /// the values are not a single snapshot.
+    pub fn read_status_registers(&mut self) -> Result<StatusRegisters, Error<SpiE, GpioE>> {
+        let lqi = LQI(self.0.read_register(Status::LQI)?);
+        let marcstate = MARCSTATE(self.0.read_register(Status::MARCSTATE)?).marc_state();
+        let wor_time = self.get_wor_time()?;
+        let txbytes = TXBYTES(self.0.read_register(Status::TXBYTES)?);
+        let rxbytes = RXBYTES(self.0.read_register(Status::RXBYTES)?);
+        Ok(StatusRegisters {
+            partnum: self.0.read_register(Status::PARTNUM)?,
+            version: self.0.read_register(Status::VERSION)?,
+            freq_offset: freqest_to_hz(self.0.read_register(Status::FREQEST)?),
+            crc_ok: lqi.crc_ok() == 1,
+            lqi: lqi.lqi(),
+            rssi_dbm: rssi_to_dbm(self.0.read_register(Status::RSSI)?),
+            machine_state: MachineState::try_from(marcstate).map_err(Error::UnknownMachineState)?,
+            wor_time,
+            pkt_status: PktStatus::from(self.0.read_register(Status::PKTSTATUS)?),
+            tx_underflow: txbytes.txfifo_underflow() == 1,
+            tx_bytes: txbytes.num_txbytes(),
+            rx_overflow: rxbytes.rxfifo_overflow() == 1,
+            rx_bytes: rxbytes.num_rxbytes(),
+        })
+    }
+
+    /// Enter the SLEEP power-down state once CS is deasserted.
+    ///
+    /// Configuration is retained in SLEEP, except for the test registers and PATABLE entries
+    /// other than the first, but lost on reset. Any SPI access wakes the chip, see `wake`.
+    pub fn sleep(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)?;
+        self.0.write_strobe(Command::SPWD)?;
+        Ok(())
+    }
+
+    /// Turn off the crystal oscillator once CS is deasserted. Any SPI access wakes the chip.
+    pub fn xtal_off(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Idle)?;
+        self.0.write_strobe(Command::SXOFF)?;
+        Ok(())
+    }
+
+    /// Wake the chip from SLEEP or XOFF by asserting CS, and wait until it is ready in IDLE.
+    ///
+    /// Gives up with `Error::Timeout` if CHIP_RDYn is not cleared, or IDLE not reached, within
+    /// `CHIP_READY_POLLS` accesses, such as when the radio is not powered.
+    pub fn wake(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        self.await_chip_ready()?;
+        self.await_machine_state_timeout(MachineState::IDLE, Some(CHIP_READY_POLLS))
+    }
+
+    /// Issue SNOP strobes until the status byte reports the chip ready, which also wakes it.
+    fn await_chip_ready(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        for _ in 0..CHIP_READY_POLLS {
+            self.0.write_strobe(Command::SNOP)?;
+            if self.last_status().is_some_and(|status| status.chip_ready) {
+                return Ok(());
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    /// Reset the chip, restoring all registers to their reset values, and wait until it is
+    /// ready in IDLE.
+    ///
+    /// Follows the manual reset sequence: CS is strobed and held high for `RESET_CS_HIGH_US`,
+    /// and SRES issued once CHIP_RDYn is cleared. Gives up with `Error::Timeout`, like `wake`,
+    /// if the chip does not become ready.
+    pub fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<SpiE, GpioE>> {
+        self.0.transaction(&mut [])?;
+        delay.delay_us(RESET_CS_HIGH_US);
+        self.await_chip_ready()?;
+        self.0.write_strobe(Command::SRES)?;
+        self.1.rx_len = None;
+        self.await_chip_ready()?;
+        self.await_machine_state_timeout(MachineState::IDLE, Some(CHIP_READY_POLLS))
+    }
+
+    /// Write a complete radio configuration in one pass. The radio should be in IDLE.
+    ///
+    /// The configuration is validated before anything is written, and registers shared by
+    /// several settings, such as MDMCFG2, are written once. A data rate, deviation or channel
+    /// bandwidth outside the `lowlevel::convert` limits, such as `DATA_RATE_MIN`, or an invalid
+    /// AGC setting returns `Error::InvalidConfig`.
+    pub fn apply(&mut self, config: &RadioConfig) -> Result<(), Error<SpiE, GpioE>> {
+        if !FREQUENCY_BANDS.iter().any(|band| band.contains(&config.frequency)) {
+            return Err(Error::FrequencyOutOfRange);
+        }
+        if config.modulation == Modulation::FourFrequencyShiftKeying
+            && (config.manchester || config.data_rate > FSK4_DATA_RATE_MAX)
+        {
+            return Err(Error::InvalidConfig);
+        }
+        if !(DATA_RATE_MIN..=DATA_RATE_MAX).contains(&config.data_rate)
+            || !(DEVIATION_MIN..=DEVIATION_MAX).contains(&config.deviation)
+            || !(CHANBW_MIN..=CHANBW_MAX).contains(&config.chanbw)
+            || config.agc.is_some_and(|agc| !agc.is_valid())
+        {
+            return Err(Error::InvalidConfig);
+        }
+        let band = Band::from_frequency(config.frequency);
+        let pa = match config.tx_power_dbm {
+            Some(dbm) => Some(dbm_to_patable(band, dbm).ok_or(Error::InvalidPower)?),
+            None => None,
+        };
+
+        let (freq0, freq1, freq2) = from_frequency(config.frequency);
+        self.0.write_register(Config::FREQ2, freq2)?;
+        self.0.write_register(Config::FREQ1, freq1)?;
+        self.0.write_register(Config::FREQ0, freq0)?;
+        self.1.band = Some(band);
+
+        let (drate_m, drate_e) = from_drate(config.data_rate);
+        let (chanbw_m, chanbw_e) = from_chanbw(config.chanbw);
+        self.0.write_register(
+            Config::MDMCFG4,
+            MDMCFG4::default().chanbw_e(chanbw_e).chanbw_m(chanbw_m).drate_e(drate_e).bits(),
+        )?;
+        self.0.write_register(Config::MDMCFG3, MDMCFG3::default().drate_m(drate_m).bits())?;
+
+        let (deviation_m, deviation_e) = from_deviation(config.deviation);
+        self.0.write_register(
+            Config::DEVIATN,
+            DEVIATN::default().deviation_m(deviation_m).deviation_e(deviation_e).bits(),
+        )?;
+
+        let (sync_check, word) = config.sync_mode.check_and_word();
+        self.0.write_register(
+            Config::MDMCFG2,
+            MDMCFG2::default()
+                .dem_dcfilt_off(!config.dc_filter as u8)
+                .mod_format(config.modulation.mod_format().value())
+                .manchester_en(config.manchester as u8)
+                .sync_mode(sync_check.value())
+                .bits(),
+        )?;
+        self.0.write_register(Config::SYNC1, ((word >> 8) & 0xff) as u8)?;
+        self.0.write_register(Config::SYNC0, (word & 0xff) as u8)?;
+
+        let num_preamble = config.preamble.num_preamble().value();
+        self.0.modify_register(Config::MDMCFG1, |r| {
+            MDMCFG1(r).modify().num_preamble(num_preamble).bits()
+        })?;
+
+        let (length_config, pktlen) = config.packet_length.config_and_len();
+        self.0.modify_register(Config::PKTCTRL0, |r| {
+            PKTCTRL0(r)
+                .modify()
+                .white_data(config.whitening as u8)
+                .crc_en(config.crc as u8)
+                .length_config(length_config.value())
+                .bits()
+        })?;
+        self.0.write_register(Config::PKTLEN, pktlen)?;
+
+        let (adr_chk, addr) = config.address_filter.check_and_addr();
+        self.0.modify_register(Config::PKTCTRL1, |r| {
+            PKTCTRL1(r).modify().adr_chk(adr_chk.value()).bits()
+        })?;
+        self.0.write_register(Config::ADDR, addr)?;
+
+        if let Some(pa) = pa {
+            let mut patable = [0u8; 8];
+            self.0.read_patable(&mut patable)?;
+            patable[0] = pa;
+            self.0.write_patable(&patable)?;
+        }
+        if let Some(agc) = config.agc {
+            self.set_agc(agc)?;
+        }
+        Ok(())
+    }
+
+    /// Reset the chip, and configure some default settings, to be removed in the future.
+    #[rustfmt::skip]
+    pub fn set_defaults<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<SpiE, GpioE>> {
+        self.reset(delay)?;
+
+        self.0.write_register(Config::PKTCTRL0, PKTCTRL0::default()
+            .white_data(0).bits()
+        )?;
+
+        self.set_if_frequency(203_125)?;
+
+        self.set_dc_filter(false)?;
+
+        self.set_autocal_mode(AutoCalibration::FROM_IDLE)?;
+
+        self.set_max_lna_gain(0x04)?;
+
+        Ok(())
+    }
+
+    /// Flush the RX FIFO, entering IDLE first unless the RX FIFO has overflowed.
+    pub fn flush_rx(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        match self.get_machine_state()? {
+            MachineState::IDLE | MachineState::RXFIFO_OVERFLOW => {}
+            _ => self.set_radio_mode(RadioMode::Idle)?,
+        }
+        self.0.write_strobe(Command::SFRX)?;
+        self.1.rx_len = None;
+        Ok(())
+    }
+
+    /// Flush the TX FIFO, entering IDLE first unless the TX FIFO has underflowed.
+    pub fn flush_tx(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        match self.get_machine_state()? {
+            MachineState::IDLE | MachineState::TXFIFO_UNDERFLOW => {}
+            _ => self.set_radio_mode(RadioMode::Idle)?,
+        }
+        self.0.write_strobe(Command::SFTX)?;
+        Ok(())
+    }
+
+    /// Recover from RX FIFO overflow or TX FIFO underflow, flushing the FIFO and returning to
+    /// IDLE.
+    ///
+    /// Returns whether the radio was in one of those states, which it won't leave by itself.
+    pub fn recover(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        let strobe = match self.get_machine_state()? {
+            MachineState::RXFIFO_OVERFLOW => Command::SFRX,
+            MachineState::TXFIFO_UNDERFLOW => Command::SFTX,
+            _ => return Ok(false),
+        };
+        self.0.write_strobe(strobe)?;
+        if matches!(strobe, Command::SFRX) {
+            self.1.rx_len = None;
+        }
+        self.await_machine_state(MachineState::IDLE)?;
+        Ok(true)
+    }
+
+    /// The current state of the main radio control state machine.
+    pub fn get_machine_state(&mut self) -> Result<MachineState, Error<SpiE, GpioE>> {
+        let marcstate = MARCSTATE(self.0.read_register(Status::MARCSTATE)?);
+        MachineState::try_from(marcstate.marc_state()).map_err(Error::UnknownMachineState)
+    }
+
+    /// Wait for the radio to reach IDLE, such as after a manual strobe, giving up with
+    /// `Error::Timeout` if it has not within `max_polls` reads of MARCSTATE.
+    pub fn poll_until_idle(&mut self, max_polls: u32) -> Result<(), Error<SpiE, GpioE>> {
+        self.await_machine_state_timeout(MachineState::IDLE, Some(max_polls))
+    }
+
+    fn await_machine_state(&mut self, target: MachineState) -> Result<(), Error<SpiE, GpioE>> {
+        self.await_machine_state_timeout(target, None)
+    }
+
+    fn await_machine_state_timeout(
+        &mut self,
+        target: MachineState,
+        max_polls: Option<u32>,
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        let mut polls = 0;
+        loop {
+            let marcstate = MARCSTATE(self.0.read_register(Status::MARCSTATE)?);
+            if target.value() == marcstate.marc_state() {
+                break;
+            }
+            polls += 1;
+            if max_polls.is_some_and(|max| polls >= max) {
+                return Err(Error::Timeout);
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of bytes in the TX FIFO, waiting to be transmitted.
+    ///
+    /// On TX FIFO underflow the FIFO is flushed, and `Error::TxUnderflow` returned.
+    pub fn tx_bytes_available(&mut self) -> Result<u8, Error<SpiE, GpioE>> {
+        let txbytes = TXBYTES(self.0.read_register(Status::TXBYTES)?);
+        if txbytes.txfifo_underflow() == 1 {
+            self.flush_tx()?;
+            return Err(Error::TxUnderflow);
+        }
+        Ok(txbytes.num_txbytes())
+    }
+
+    fn crc_enabled(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        Ok(PKTCTRL0(self.0.read_register(Config::PKTCTRL0)?).crc_en() == 1)
+    }
+
+    fn append_status_enabled(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        Ok(PKTCTRL1(self.0.read_register(Config::PKTCTRL1)?).append_status() == 1)
+    }
+
+    /// Check whether a complete packet is waiting in the RX FIFO, without altering the FIFO.
+    ///
+    /// Returns false on RX FIFO overflow, which `receive` reports as `Error::RxOverflow`.
+    pub fn is_data_available(&mut self) -> Result<bool, Error<SpiE, GpioE>> {
+        self.packet_available(false)
+    }
+
+    /// Check, without blocking, whether a complete packet is waiting in the RX FIFO.
+    ///
+    /// On RX FIFO overflow, returns false if `overflow_err` is false, otherwise flushes the FIFO
+    /// and returns `Error::RxOverflow`.
+    fn packet_available(&mut self, overflow_err: bool) -> Result<bool, Error<SpiE, GpioE>> {
+        let rxbytes = RXBYTES(self.0.read_register(Status::RXBYTES)?);
+        if rxbytes.rxfifo_overflow() == 1 {
+            if !overflow_err {
+                return Ok(false);
+            }
+            self.flush_rx()?;
+            return Err(Error::RxOverflow);
+        }
+        if rxbytes.num_rxbytes() == 0 {
+            return Ok(false);
+        }
+        // The start of frame delimiter stays asserted until the end of the packet.
+        let pktstatus = PKTSTATUS(self.0.read_register(Status::PKTSTATUS)?);
+        Ok(pktstatus.sfd() == 0)
+    }
+
+    /// Receive a packet, blocking until one arrives.
+    pub fn receive(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        self.receive_polls(addr, buf, None)
+    }
+
+    /// Receive a packet, giving up with `Error::Timeout` if none is received within
+    /// `max_polls` reads of the RX FIFO status.
+    pub fn receive_timeout(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+        max_polls: u32,
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        self.receive_polls(addr, buf, Some(max_polls))
+    }
+
+    fn receive_polls(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+        max_polls: Option<u32>,
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        let mut polls = 0;
+        loop {
+            if let Some(packet) = self.try_receive(addr, buf)? {
+                return Ok(packet);
+            }
+            polls += 1;
+            if max_polls.is_some_and(|max| polls >= max) {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+
+    /// Receive a packet regardless of its address, reporting whether it passed the configured
+    /// `AddressFilter`.
+    ///
+    /// Packets dropped by the hardware address check never reach the RX FIFO, so the check is
+    /// disabled while receiving, and the filter applied to the received address instead. The
+    /// address check configuration is restored afterwards.
+    pub fn receive_with_filter_info(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<(ReceivedPacket, bool), Error<SpiE, GpioE>> {
+        use lowlevel::types::AddressCheck as AC;
+
+        let pktctrl1 = self.0.read_register(Config::PKTCTRL1)?;
+        let device = self.0.read_register(Config::ADDR)?;
+        self.0.write_register(
+            Config::PKTCTRL1,
+            PKTCTRL1(pktctrl1).modify().adr_chk(AC::DISABLED.value()).bits(),
+        )?;
+        let result = self.receive(addr, buf);
+        self.0.write_register(Config::PKTCTRL1, pktctrl1)?;
+        let packet = result?;
+
+        let adr_chk = PKTCTRL1(pktctrl1).adr_chk();
+        let matched = if adr_chk == AC::SELF.value() {
+            *addr == device
+        } else if adr_chk == AC::SELF_LOW_BROADCAST.value() {
+            *addr == device || *addr == 0x00
+        } else if adr_chk == AC::SELF_HIGH_LOW_BROADCAST.value() {
+            *addr == device || *addr == 0x00 || *addr == 0xFF
+        } else {
+            true
+        };
+        Ok((packet, matched))
+    }
+
+    /// Receive a packet if a complete one is waiting in the RX FIFO, without blocking.
+    ///
+    /// Returns `Ok(None)` if nothing, or only part of a packet, has been received yet.
+    pub fn try_receive(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<Option<ReceivedPacket>, Error<SpiE, GpioE>> {
+        match self.try_read_packet(addr, buf)? {
+            Some(packet) if !packet.crc_ok && self.crc_enabled()? => Err(Error::CrcMismatch),
+            packet => Ok(packet),
+        }
+    }
+
+    /// Receive a packet, blocking until one arrives, keeping it even if its CRC did not match.
+    ///
+    /// Unlike `receive`, a CRC mismatch is reported through `ReceivedPacket::crc_ok` with the
+    /// payload left in `buf`, for sniffing or error correction at a higher layer. Packets are
+    /// only kept if CRC autoflush is disabled, see `set_crc_autoflush`.
+    pub fn receive_allow_crc_error(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<ReceivedPacket, Error<SpiE, GpioE>> {
+        loop {
+            if let Some(packet) = self.try_read_packet(addr, buf)? {
+                return Ok(packet);
+            }
+        }
+    }
+
+    fn try_read_packet(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<Option<ReceivedPacket>, Error<SpiE, GpioE>> {
+        if !self.packet_available(true)? {
+            return Ok(None);
+        }
+
+        let mut length = 0u8;
+        let count = match self.1.rx_len.take() {
+            // The length byte was already taken by `receive_all`.
+            Some(len) => {
+                length = len;
+                let mut byte = [0u8];
+                self.0.read_burst(Command::FIFO, &mut byte)?;
+                *addr = byte[0];
+                let count = (len as usize).saturating_sub(1).min(buf.len());
+                if count > 0 {
+                    self.0.read_burst(Command::FIFO, &mut buf[..count])?;
+                }
+                count
+            }
+            None => self.0.read_fifo(addr, &mut length, buf)?,
+        };
+        if count < (length as usize).saturating_sub(1) {
+            self.flush_rx()?;
+            return Err(Error::RxOverflow);
+        }
+        // Appended status bytes follow the payload.
+        let (rssi, lqi) = if self.append_status_enabled()? {
+            let mut status = [0u8; 2];
+            self.0.read_burst(Command::FIFO, &mut status)?;
+            (status[0], status[1])
+        } else {
+            (self.0.read_register(Status::RSSI)?, self.0.read_register(Status::LQI)?)
+        };
+        // The whole packet has been read, any following one is kept in continuous RX.
+        if !self.rx_continuous_enabled()? {
+            self.flush_rx()?;
+        }
+
+        let info = PacketInfo {
+            rssi_dbm: rssi_to_dbm(rssi),
+            lqi: LQI(lqi).lqi(),
+            crc_ok: LQI(lqi).crc_ok() == 1,
+        };
+        self.1.packet_info = Some(info);
+
+        Ok(Some(ReceivedPacket {
+            len: length,
+            rssi_dbm: info.rssi_dbm,
+            lqi: info.lqi,
+            crc_ok: info.crc_ok,
+        }))
+    }
+
+    /// Read every packet waiting in the RX FIFO, calling `handler` with the address byte and
+    /// payload of each, without flushing the FIFO.
+    ///
+    /// Several small packets can queue up back to back when RXOFF_MODE stays in RX, see
+    /// `set_rx_continuous`, and `receive` only returns one of them per call. Nothing is read
+    /// while a packet is still being received, and reading stops at a packet that arrived
+    /// after that check and is not yet complete, to be read by the next call. With appended
+    /// status bytes, packets failing the CRC check are skipped, and `last_packet_info` is
+    /// updated for each packet. Returns the number of packets passed to `handler`.
+    ///
+    /// A length byte too large for the packet to fit in the RX FIFO flushes it, and returns
+    /// `Error::InvalidPacketLength`.
+    pub fn receive_all<F>(&mut self, mut handler: F) -> Result<usize, Error<SpiE, GpioE>>
+    where
+        F: FnMut(u8, &[u8]),
+    {
+        if !self.packet_available(true)? {
+            return Ok(0);
+        }
+        let status_len = if self.append_status_enabled()? {
+            2
+        } else {
+            0
+        };
+        let check_crc = status_len > 0 && self.crc_enabled()?;
+        let mut remaining = RXBYTES(self.0.read_register(Status::RXBYTES)?).num_rxbytes() as usize;
+
+        let mut count = 0;
+        let mut buf = [0u8; FIFO_SIZE];
+        loop {
+            let len = match self.1.rx_len.take() {
+                Some(len) => len as usize,
+                None if remaining == 0 => break,
+                None => {
+                    self.0.read_burst(Command::FIFO, &mut buf[..1])?;
+                    remaining -= 1;
+                    buf[0] as usize
+                }
+            };
+            if 1 + len + status_len > FIFO_SIZE {
+                self.flush_rx()?;
+                return Err(Error::InvalidPacketLength);
+            }
+            if len + status_len > remaining {
+                // The rest of the packet is still being received.
+                self.1.rx_len = Some(len as u8);
+                break;
+            }
+            self.0.read_burst(Command::FIFO, &mut buf[..len + status_len])?;
+            remaining -= len + status_len;
+
+            let crc_ok = if status_len > 0 {
+                let (rssi, lqi) = (buf[len], buf[len + 1]);
+                let info = PacketInfo {
+                    rssi_dbm: rssi_to_dbm(rssi),
+                    lqi: LQI(lqi).lqi(),
+                    crc_ok: LQI(lqi).crc_ok() == 1,
+                };
+                self.1.packet_info = Some(info);
+                info.crc_ok
+            } else {
+                true
+            };
+            if len > 0 && (crc_ok || !check_crc) {
+                handler(buf[0], &buf[1..len]);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Signal quality of the last packet read from the RX FIFO, or `None` if no packet has been
+    /// received yet.
+    ///
+    /// Cached when the packet is read, also if its CRC did not match, so it does not race
+    /// against the next packet like `get_rssi_dbm` and `get_lqi`.
+    pub fn last_packet_info(&self) -> Option<PacketInfo> {
+        self.1.packet_info
+    }
+
+    /// Read a packet from the RX FIFO once woken by the interrupt set up by
+    /// `enable_packet_interrupt`, without polling.
+    ///
+    /// Returns `Ok(None)` if no packet is waiting, as the pin also de-asserts when a packet is
+    /// discarded by address or length filtering.
+    pub fn receive_on_interrupt(
+        &mut self,
+        addr: &mut u8,
+        buf: &mut [u8],
+    ) -> Result<Option<ReceivedPacket>, Error<SpiE, GpioE>> {
+        self.try_receive(addr, buf)
+    }
+
+    /// Receive a single packet of exactly `out.len()` bytes, draining the RX FIFO while
+    /// receiving.
+    ///
+    /// The packet is received in infinite packet length mode, switching to fixed length mode
+    /// for its final part. GDO0 signals the RX FIFO threshold, see `set_fifo_threshold`, during
+    /// the reception. The FIFO is drained each time it fills to the threshold, which must leave
+    /// enough of the 64 bytes free for what arrives while draining: at 38.4 kBaud a byte arrives
+    /// every ~208 µs. Appended status bytes are discarded, and the packet length and GDO0
+    /// configurations are restored afterwards.
+    pub fn receive_stream(&mut self, out: &mut [u8]) -> Result<(), Error<SpiE, GpioE>> {
+        if out.is_empty() {
+            return Err(Error::InvalidPacketLength);
+        }
+
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        let pktctrl0 = self.0.read_register(Config::PKTCTRL0)?;
+        let pktlen = self.0.read_register(Config::PKTLEN)?;
+
+        self.flush_rx()?;
+        self.set_gdo0_config(GdoCfg::RX_FIFO_FILLED_END_OF_PKT)?;
+        self.0.write_register(Config::PKTLEN, (out.len() % 256) as u8)?;
+        let result = self.drain(out);
+
+        self.flush_rx()?;
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        self.0.write_register(Config::PKTCTRL0, pktctrl0)?;
+        self.0.write_register(Config::PKTLEN, pktlen)?;
+        result
+    }
+
+    fn drain(&mut self, out: &mut [u8]) -> Result<(), Error<SpiE, GpioE>> {
+        let mut read = 0;
+        let mut fixed = self.switch_to_fixed(out.len())?;
+        self.0.write_strobe(Command::SRX)?;
+
+        while read < out.len() {
+            if self.gdo0_is_low()? {
+                continue;
+            }
+            let rxbytes = RXBYTES(self.0.read_register(Status::RXBYTES)?);
+            if rxbytes.rxfifo_overflow() == 1 {
+                return Err(Error::RxOverflow);
+            }
+
+            // The last byte in the FIFO may only be read once the whole packet is received.
+            let remaining = out.len() - read;
+            let available = rxbytes.num_rxbytes() as usize;
+            let count = if available >= remaining {
+                remaining
+            } else {
+                available.saturating_sub(1)
+            };
+            self.0.read_burst(Command::FIFO, &mut out[read..read + count])?;
+            read += count;
+
+            if !fixed {
+                fixed = self.switch_to_fixed(out.len() - read)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Select infinite packet length mode, or fixed length mode once less than 256 bytes of the
+    /// packet remain, as the byte counter wraps at 256. Returns whether fixed mode was selected.
+    fn switch_to_fixed(&mut self, remaining: usize) -> Result<bool, Error<SpiE, GpioE>> {
+        use lowlevel::types::LengthConfig as LC;
+
+        let fixed = remaining < 256;
+        let format = if fixed {
+            LC::FIXED
+        } else {
+            LC::INFINITE
+        };
+        self.0.modify_register(Config::PKTCTRL0, |r| {
+            PKTCTRL0(r).modify().length_config(format.value()).bits()
+        })?;
+        Ok(fixed)
+    }
+
+    /// Listen before talk, transmitting only if no carrier is sensed on the channel.
+    ///
+    /// Waits `RSSI_SETTLE_US` in RX for carrier sense to become valid before checking it.
+    /// Returns `Error::ChannelBusy`, leaving the radio in IDLE, if a carrier is sensed.
+    pub fn transmit_if_clear<D: DelayNs>(
+        &mut self,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        self.set_radio_mode(RadioMode::Receive)?;
+        // Wait for RSSI, and thereby carrier sense, to become valid.
+        delay.delay_us(RSSI_SETTLE_US);
+        if self.carrier_sense()? {
+            self.set_radio_mode(RadioMode::Idle)?;
+            return Err(Error::ChannelBusy);
+        }
+        self.transmit(payload, delay)
+    }
+
+    /// Transmit a packet, and wait until it has been sent.
+    ///
+    /// The payload, including any address byte, must match the configured `PacketLength`, and
+    /// fit the TX FIFO, see `MAX_TX`. In variable length mode the length byte is prepended to
+    /// the payload.
+    ///
+    /// Returns the number of bytes sent from the TX FIFO, including any length byte.
+    pub fn transmit<D: DelayNs>(
+        &mut self,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        self.transmit_packet(None, payload, delay)
+    }
+
+    /// Transmit a packet to `dest_addr`, and wait until it has been sent.
+    ///
+    /// The address byte is written after any length byte, and before the payload, as expected
+    /// by a receiver with address filtering. Together the address and payload must match the
+    /// configured `PacketLength`.
+    pub fn transmit_addressed<D: DelayNs>(
+        &mut self,
+        dest_addr: u8,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        self.transmit_packet(Some(dest_addr), payload, delay)
+    }
+
+    /// Transmit a packet to the broadcast address, and wait until it has been sent.
+    ///
+    /// Receivers filtering with `AddressFilter::DeviceLowBroadcast` or
+    /// `AddressFilter::DeviceHighLowBroadcast` accept it, while `AddressFilter::Device` drops it.
+    pub fn transmit_broadcast<D: DelayNs>(
+        &mut self,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        self.transmit_addressed(BROADCAST_ADDR, payload, delay)
+    }
+
+    /// Wait until the sync word has been sent, or received, as signalled by a GDO pin configured
+    /// for SYNC_WORD (0x06), such as by `enable_packet_interrupt`.
+    ///
+    /// The pin level depends on the polarity set by the GDO inversion bit of IOCFG2 or IOCFG0:
+    /// SYNC_WORD drives the pin high once the sync word is sent, or low when inverted. The
+    /// polarity is read from the configuration rather than assumed. GDO2 is used if both pins
+    /// are configured, and `Error::InvalidConfig` is returned if neither is. Gives up with
+    /// `Error::Timeout` after `max_polls` reads of the pin.
+    pub fn wait_for_sync_sent(&mut self, max_polls: u32) -> Result<(), Error<SpiE, GpioE>> {
+        let sync_word = GdoCfg::SYNC_WORD.value();
+        let iocfg2 = IOCFG2(self.0.read_register(Config::IOCFG2)?);
+        let iocfg0 = IOCFG0(self.0.read_register(Config::IOCFG0)?);
+        let (pin, inverted) = if iocfg2.gdo2_cfg() == sync_word {
+            (GdoPin::Gdo2, iocfg2.gdo2_inv() == 1)
+        } else if iocfg0.gdo0_cfg() == sync_word {
+            (GdoPin::Gdo0, iocfg0.gdo0_inv() == 1)
+        } else {
+            return Err(Error::InvalidConfig);
+        };
+
+        for _ in 0..max_polls {
+            let high = match pin {
+                GdoPin::Gdo2 => self.gdo2_is_high()?,
+                _ => self.gdo0_is_high()?,
+            };
+            if high != inverted {
+                return Ok(());
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    /// Transmit a packet, and wait up to `timeout_us` in RX for an acknowledgement, retrying up
+    /// to `retries` times before giving up with `Error::NoAck`.
+    ///
+    /// `is_ack` is called with the address byte and payload of every packet received while
+    /// waiting, and returns whether it acknowledges the packet. Packets failing the CRC check
+    /// are ignored. Returns the number of bytes sent per transmission, see `transmit`, and
+    /// leaves the radio in IDLE.
+    pub fn transmit_with_retries<D, F>(
+        &mut self,
+        payload: &[u8],
+        retries: u8,
+        timeout_us: u32,
+        delay: &mut D,
+        mut is_ack: F,
+    ) -> Result<usize, Error<SpiE, GpioE>>
+    where
+        D: DelayNs,
+        F: FnMut(u8, &[u8]) -> bool,
+    {
+        for _ in 0..=retries {
+            let written = self.transmit(payload, delay)?;
+            self.set_radio_mode(RadioMode::Receive)?;
+            if self.await_ack(timeout_us, delay, &mut is_ack)? {
+                return Ok(written);
+            }
+        }
+        Err(Error::NoAck)
+    }
+
+    fn await_ack<D, F>(
+        &mut self,
+        timeout_us: u32,
+        delay: &mut D,
+        is_ack: &mut F,
+    ) -> Result<bool, Error<SpiE, GpioE>>
+    where
+        D: DelayNs,
+        F: FnMut(u8, &[u8]) -> bool,
+    {
+        let mut addr = 0u8;
+        let mut buf = [0u8; MAX_TX];
+        let mut waited = 0;
+        loop {
+            match self.try_receive(&mut addr, &mut buf) {
+                Ok(Some(packet)) => {
+                    let len = (packet.len as usize).saturating_sub(1).min(buf.len());
+                    if is_ack(addr, &buf[..len]) {
+                        self.set_radio_mode(RadioMode::Idle)?;
+                        return Ok(true);
+                    }
+                    // Reading the packet left RX.
+                    self.set_radio_mode(RadioMode::Receive)?;
+                }
+                Ok(None) => {}
+                Err(Error::CrcMismatch) => self.set_radio_mode(RadioMode::Receive)?,
+                Err(e) => return Err(e),
+            }
+            if waited >= timeout_us {
+                self.set_radio_mode(RadioMode::Idle)?;
+                return Ok(false);
+            }
+            delay.delay_us(ACK_POLL_US);
+            waited += ACK_POLL_US;
+        }
+    }
+
+    fn transmit_packet<D: DelayNs>(
+        &mut self,
+        addr: Option<u8>,
+        payload: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        let variable = self.validate_payload(addr, payload)?;
+        self.flush_tx()?;
+        delay.delay_us(100);
+        let written = self.write_payload(addr, payload, variable)?;
+        self.0.write_strobe(Command::STX)?;
+        self.await_transmitted()?;
+        Ok(written)
+    }
+
+    /// Check that the address byte, if any, and `payload` match the configured `PacketLength`,
+    /// returning whether a length byte must be prepended.
+    fn validate_payload(
+        &mut self,
+        addr: Option<u8>,
+        payload: &[u8],
+    ) -> Result<bool, Error<SpiE, GpioE>> {
+        let len = addr.is_some() as usize + payload.len();
+        let (valid, expected) = match self.get_packet_length()? {
+            PacketLength::Fixed(n) => (len == n as usize && len <= MAX_TX, PacketLength::Fixed(n)),
+            PacketLength::Variable(max) => {
+                let max = max.min(MAX_TX as u8 - 1);
+                (len > 0 && len <= max as usize, PacketLength::Variable(max))
+            }
+            PacketLength::Infinite => (false, PacketLength::Infinite),
+        };
+        if !valid {
+            return Err(Error::InvalidLength {
+                expected,
+                actual: len,
+            });
+        }
+        Ok(matches!(expected, PacketLength::Variable(_)))
+    }
+
+    /// Write the length byte if `variable`, the address byte if any, and `payload` to the TX
+    /// FIFO, returning the number of bytes written.
+    fn write_payload(
+        &mut self,
+        addr: Option<u8>,
+        payload: &[u8],
+        variable: bool,
+    ) -> Result<usize, Error<SpiE, GpioE>> {
+        let len = addr.is_some() as usize + payload.len();
+        if variable {
+            self.0.write_register(Command::FIFO, len as u8)?;
+        }
+        if let Some(addr) = addr {
+            self.0.write_register(Command::FIFO, addr)?;
+        }
+        self.0.write_burst(Command::FIFO, payload)?;
+        Ok(variable as usize + len)
+    }
+
+    /// Transmit `data` as a single packet of any length, refilling the TX FIFO while sending.
+    ///
+    /// The packet is sent in infinite packet length mode, switching to fixed length mode for
+    /// its final part. No length byte is sent, so the receiver must know the length. GDO0
+    /// signals the TX FIFO threshold during the transmission, and the packet length and GDO0
+    /// configurations are restored afterwards.
+    pub fn transmit_stream(&mut self, data: &[u8]) -> Result<(), Error<SpiE, GpioE>> {
+        if data.is_empty() {
+            return Err(Error::InvalidPacketLength);
+        }
+
+        let iocfg0 = self.0.read_register(Config::IOCFG0)?;
+        let pktctrl0 = self.0.read_register(Config::PKTCTRL0)?;
+        let pktlen = self.0.read_register(Config::PKTLEN)?;
+
+        self.flush_tx()?;
+        self.set_gdo0_config(GdoCfg::TX_FIFO_FILLED)?;
+        self.0.write_register(Config::PKTLEN, (data.len() % 256) as u8)?;
+        let result = self.stream(data);
+
+        self.0.write_register(Config::IOCFG0, iocfg0)?;
+        self.0.write_register(Config::PKTCTRL0, pktctrl0)?;
+        self.0.write_register(Config::PKTLEN, pktlen)?;
+        result
+    }
+
+    fn stream(&mut self, data: &[u8]) -> Result<(), Error<SpiE, GpioE>> {
+        let mut written = 0;
+        let mut fixed = false;
+        let mut started = false;
+        while written < data.len() {
+            // Refill once the FIFO has drained below the threshold.
+            if started && self.gdo0_is_high()? {
+                continue;
+            }
+            let room = FIFO_SIZE - self.tx_bytes_available()? as usize;
+            let chunk = &data[written..data.len().min(written + room)];
+            self.0.write_burst(Command::FIFO, chunk)?;
+            written += chunk.len();
+
+            // Up to a full FIFO of the written bytes may not have been sent yet.
+            if !fixed {
+                fixed = self.switch_to_fixed(data.len() - written + FIFO_SIZE)?;
+            }
+            if !started {
+                self.0.write_strobe(Command::STX)?;
+                started = true;
+            }
+        }
+        self.await_transmitted()
+    }
+
+    /// Wait until the radio has entered, and then left, TX, giving up with `Error::Timeout` if
+    /// it has not within `TX_DONE_POLLS` polls.
+    ///
+    /// With TXOFF_MODE TX the radio never leaves TX, so the packet is considered sent once the
+    /// TX FIFO has drained.
+    fn await_transmitted(&mut self) -> Result<(), Error<SpiE, GpioE>> {
+        let tx = MachineState::TX.value()..=MachineState::TX_END.value();
+        let mcsm1 = MCSM1(self.0.read_register(Config::MCSM1)?);
+        let stays_in_tx = mcsm1.txoff_mode() == lowlevel::types::TxOffMode::TX.value();
+        let mut started = false;
+        for _ in 0..TX_DONE_POLLS {
+            let marcstate = MARCSTATE(self.0.read_register(Status::MARCSTATE)?).marc_state();
+            if marcstate == MachineState::TXFIFO_UNDERFLOW.value() {
+                self.flush_tx()?;
+                return Err(Error::TxUnderflow);
+            }
+            let transmitting = tx.contains(&marcstate);
+            if started && !transmitting {
+                return Ok(());
+            }
+            if transmitting && stays_in_tx && self.tx_bytes_available()? == 0 {
+                return Ok(());
+            }
+            started |= transmitting;
+        }
+        Err(Error::Timeout)
+    }
+}
+
+/// Status byte returned by the chip at the start of every SPI access.
+#[derive(Debug, Clone, Copy)]
+pub struct ChipStatus {
+    /// The crystal is running, and the chip is ready for access.
+    pub chip_ready: bool,
+    /// Main radio control state.
+    pub state: ChipState,
+    /// Bytes available in the RX FIFO after a read, or free in the TX FIFO after a write,
+    /// saturating at 15.
+    pub fifo_bytes_available: u8,
+}
+
+impl From<u8> for ChipStatus {
+    fn from(status: u8) -> Self {
+        let state = match (status >> 4) & 0x07 {
+            0 => ChipState::Idle,
+            1 => ChipState::Receive,
+            2 => ChipState::Transmit,
+            3 => ChipState::FastTxReady,
+            4 => ChipState::Calibrate,
+            5 => ChipState::Settling,
+            6 => ChipState::RxFifoOverflow,
+            _ => ChipState::TxFifoUnderflow,
+        };
+        ChipStatus {
+            chip_ready: status & 0x80 == 0,
+            state,
+            fifo_bytes_available: status & 0x0F,
+        }
+    }
+}
+
+/// Real-time packet and channel status flags, from PKTSTATUS.
+#[derive(Debug, Clone, Copy)]
+pub struct PktStatus {
+    /// The last CRC comparison matched. Cleared when entering or restarting RX.
+    pub crc_ok: bool,
+    /// RSSI is above the carrier sense threshold.
+    pub carrier_sense: bool,
+    /// Preamble quality reached.
+    pub pqt_reached: bool,
+    /// Channel is clear, according to the CCA mode.
+    pub channel_clear: bool,
+    /// Sync word found, de-asserted at the end of the packet.
+    pub sync_found: bool,
+    /// Current GDO2 value.
+    pub gdo2: bool,
+    /// Current GDO0 value.
+    pub gdo0: bool,
+}
+
+impl From<u8> for PktStatus {
+    fn from(status: u8) -> Self {
+        let status = PKTSTATUS(status);
+        PktStatus {
+            crc_ok: status.crc_ok() == 1,
+            carrier_sense: status.cs() == 1,
+            pqt_reached: status.pqt_reached() == 1,
+            channel_clear: status.cca() == 1,
+            sync_found: status.sfd() == 1,
+            gdo2: status.gdo2() == 1,
+            gdo0: status.gdo0() == 1,
+        }
+    }
+}
+
+/// Parsed status registers, see `Cc1101::read_status_registers`.
+#[derive(Debug, Clone, Copy)]
+pub struct StatusRegisters {
+    pub partnum: u8,
+    pub version: u8,
+    /// Estimated frequency offset of the received signal, in Hz.
+    pub freq_offset: i32,
+    /// The last CRC comparison matched.
+    pub crc_ok: bool,
+    pub lqi: u8,
+    pub rssi_dbm: i16,
+    pub machine_state: MachineState,
+    /// WOR timer value.
+    pub wor_time: u16,
+    pub pkt_status: PktStatus,
+    pub tx_underflow: bool,
+    pub tx_bytes: u8,
+    pub rx_overflow: bool,
+    pub rx_bytes: u8,
+}
+
+/// Main radio control state, as reported in the chip status byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChipState {
+    Idle,
+    Receive,
+    Transmit,
+    /// Frequency synthesizer on, ready to transmit.
+    FastTxReady,
+    /// Frequency synthesizer calibration running.
+    Calibrate,
+    /// PLL settling.
+    Settling,
+    RxFifoOverflow,
+    TxFifoUnderflow,
+}
+
+/// A received packet, with the signal quality it was received with.
+#[derive(Debug)]
+pub struct ReceivedPacket {
+    /// Packet length, including the address byte.
+    pub len: u8,
+    /// Received signal strength, in dBm.
+    pub rssi_dbm: i16,
+    /// Link Quality Indicator.
+    pub lqi: u8,
+    /// The CRC of the packet matched.
+    pub crc_ok: bool,
+}
+
+/// Signal quality of a received packet, see `Cc1101::last_packet_info`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacketInfo {
+    /// Received signal strength, in dBm.
+    pub rssi_dbm: i16,
+    /// Link Quality Indicator.
+    pub lqi: u8,
+    /// The CRC of the packet matched.
+    pub crc_ok: bool,
+}
+
+/// ISM band, which band dependent settings such as the PATABLE values depend on.
+///
+/// The ranges are those the CC1101 supports, see `Band::from_frequency` for frequencies outside
+/// them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Band {
+    /// 300-348 MHz.
+    Band315,
+    /// 387-464 MHz.
+    Band433,
+    /// 779-900 MHz.
+    Band868,
+    /// 900-928 MHz.
+    Band915,
+}
+
+impl Band {
+    /// The band a carrier frequency, in Hz, falls in, or is closest to.
+    ///
+    /// Frequencies between bands snap to the nearest one, with the cut-offs at the midpoints
+    /// of the gaps: 367.5 MHz between `Band315` and `Band433`, and 621.5 MHz between `Band433`
+    /// and `Band868`. `Band868` and `Band915` meet at 900 MHz.
+    pub fn from_frequency(hz: u64) -> Self {
+        match hz {
+            0..=367_499_999 => Band::Band315,
+            367_500_000..=621_499_999 => Band::Band433,
+            621_500_000..=900_000_000 => Band::Band868,
+            _ => Band::Band915,
+        }
+    }
+}
+
+/// Trade-off between RX sensitivity and current consumption, see
+/// `Cc1101::set_sensitivity_mode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SensitivityMode {
+    /// DC blocking filter enabled, and raised LNA currents.
+    HighSensitivity,
+    /// DC blocking filter enabled, and reset LNA currents.
+    Balanced,
+    /// DC blocking filter disabled, and reset LNA currents, for data rates up to 250 kBaud.
+    LowCurrent,
+}
+
+impl SensitivityMode {
+    fn dc_filter_and_frend1(self) -> (bool, Frend1Config) {
+        match self {
+            SensitivityMode::HighSensitivity => {
+                (true, Frend1Config::default().lna_current(2).lna2mix_current(3))
+            }
+            SensitivityMode::Balanced => (true, Frend1Config::default()),
+            SensitivityMode::LowCurrent => (false, Frend1Config::default()),
+        }
+    }
+}
+
+/// Clear channel assessment configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CcaMode {
+    /// Channel is always clear.
+    Always,
+    /// Channel is clear when the RSSI is below the carrier sense threshold.
+    RssiBelowThreshold,
+    /// Channel is clear unless currently receiving a packet.
+    UnlessReceiving,
+    /// Channel is clear when the RSSI is below the carrier sense threshold, unless currently
+    /// receiving a packet.
+    RssiBelowThresholdUnlessReceiving,
+}
 
 /// Modulation format configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Modulation {
     /// 2-FSK.
     BinaryFrequencyShiftKeying,
@@ -347,43 +2561,1665 @@ pub enum Modulation {
     MinimumShiftKeying,
 }
 
-/// Packet length configuration.
-pub enum PacketLength {
-    /// Set packet length to a fixed value.
-    Fixed(u8),
-    /// Set upper bound of variable packet length.
-    Variable(u8),
-    /// Infinite packet length, streaming mode.
-    Infinite,
-}
+impl Modulation {
+    fn mod_format(self) -> ModFormat {
+        match self {
+            Modulation::BinaryFrequencyShiftKeying => ModFormat::MOD_2FSK,
+            Modulation::GaussianFrequencyShiftKeying => ModFormat::MOD_GFSK,
+            Modulation::OnOffKeying => ModFormat::MOD_ASK_OOK,
+            Modulation::FourFrequencyShiftKeying => ModFormat::MOD_4FSK,
+            Modulation::MinimumShiftKeying => ModFormat::MOD_MSK,
+        }
+    }
+}
+
+/// Preamble length configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PreambleLength {
+    /// 2 preamble bytes.
+    Bytes2,
+    /// 3 preamble bytes.
+    Bytes3,
+    /// 4 preamble bytes.
+    Bytes4,
+    /// 6 preamble bytes.
+    Bytes6,
+    /// 8 preamble bytes.
+    Bytes8,
+    /// 12 preamble bytes.
+    Bytes12,
+    /// 16 preamble bytes.
+    Bytes16,
+    /// 24 preamble bytes.
+    Bytes24,
+}
+
+impl PreambleLength {
+    fn num_preamble(self) -> NumPreamble {
+        match self {
+            PreambleLength::Bytes2 => NumPreamble::N_2,
+            PreambleLength::Bytes3 => NumPreamble::N_3,
+            PreambleLength::Bytes4 => NumPreamble::N_4,
+            PreambleLength::Bytes6 => NumPreamble::N_6,
+            PreambleLength::Bytes8 => NumPreamble::N_8,
+            PreambleLength::Bytes12 => NumPreamble::N_12,
+            PreambleLength::Bytes16 => NumPreamble::N_16,
+            PreambleLength::Bytes24 => NumPreamble::N_24,
+        }
+    }
+}
+
+/// Packet length configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PacketLength {
+    /// Set packet length to a fixed value.
+    Fixed(u8),
+    /// Set upper bound of variable packet length.
+    Variable(u8),
+    /// Infinite packet length, streaming mode.
+    Infinite,
+}
+
+impl PacketLength {
+    fn config_and_len(self) -> (LengthConfig, u8) {
+        match self {
+            PacketLength::Fixed(limit) => (LengthConfig::FIXED, limit),
+            PacketLength::Variable(max_limit) => (LengthConfig::VARIABLE, max_limit),
+            PacketLength::Infinite => (LengthConfig::INFINITE, PKTLEN::default().bits()),
+        }
+    }
+}
+
+/// Address check configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AddressFilter {
+    /// No address check.
+    Disabled,
+    /// Address check, no broadcast.
+    Device(u8),
+    /// Address check and 0 (0x00) broadcast.
+    DeviceLowBroadcast(u8),
+    /// Address check and 0 (0x00) and 255 (0xFF) broadcast.
+    DeviceHighLowBroadcast(u8),
+}
+
+impl AddressFilter {
+    fn check_and_addr(self) -> (AddressCheck, u8) {
+        match self {
+            AddressFilter::Disabled => (AddressCheck::DISABLED, ADDR::default().bits()),
+            AddressFilter::Device(addr) => (AddressCheck::SELF, addr),
+            AddressFilter::DeviceLowBroadcast(addr) => (AddressCheck::SELF_LOW_BROADCAST, addr),
+            AddressFilter::DeviceHighLowBroadcast(addr) => {
+                (AddressCheck::SELF_HIGH_LOW_BROADCAST, addr)
+            }
+        }
+    }
+}
+
+/// General purpose digital output pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GdoPin {
+    Gdo0,
+    /// Shared with the SPI SO pin, only driven while CS is high.
+    Gdo1,
+    Gdo2,
+}
+
+/// Division of the crystal clock output on a GDO pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockDivider {
+    Div1,
+    Div1_5,
+    Div2,
+    Div3,
+    Div4,
+    Div6,
+    Div8,
+    Div12,
+    Div16,
+    Div24,
+    Div32,
+    Div48,
+    Div64,
+    Div96,
+    Div128,
+    Div192,
+}
+
+/// Radio operational mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RadioMode {
+    Receive,
+    Transmit,
+    Idle,
+    /// FSTXON, with the frequency synthesizer calibrated and running, for fast RX and TX
+    /// turnaround.
+    FastReady,
+}
+
+/// Sync word configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SyncMode {
+    /// No sync word.
+    Disabled,
+    /// Match 15 of 16 bits of given sync word.
+    MatchPartial(u16),
+    /// Match 30 of 32 bits of a repetition of given sync word.
+    ///
+    /// The 32-bit sync word is always the 16-bit SYNC1:SYNC0 word sent twice, as the CC1101
+    /// has no registers for an arbitrary 32-bit word, see `SyncMode::repeated`.
+    MatchPartialRepeated(u16),
+    /// Match 16 of 16 bits of given sync word.
+    MatchFull(u16),
+}
+
+impl SyncMode {
+    /// Match 30 of 32 bits of the 32-bit sync word `word`, which must be a 16-bit word
+    /// repeated, such as `0xD391_D391`, as no other 32-bit sync words are supported.
+    pub fn repeated(word: u32) -> Option<Self> {
+        let (high, low) = ((word >> 16) as u16, word as u16);
+        if high == low {
+            Some(SyncMode::MatchPartialRepeated(low))
+        } else {
+            None
+        }
+    }
+
+    fn check_and_word(self) -> (SyncCheck, u16) {
+        let reset: u16 = (SYNC1::default().bits() as u16) << 8 | (SYNC0::default().bits() as u16);
+
+        match self {
+            SyncMode::Disabled => (SyncCheck::DISABLED, reset),
+            SyncMode::MatchPartial(word) => (SyncCheck::CHECK_15_16, word),
+            SyncMode::MatchPartialRepeated(word) => (SyncCheck::CHECK_30_32, word),
+            SyncMode::MatchFull(word) => (SyncCheck::CHECK_16_16, word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lowlevel::registers::*;
+    use crate::lowlevel::types::*;
+    use crate::mock;
+
+    #[test]
+    fn test_set_whitening() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_packet_length(crate::PacketLength::Fixed(16)).unwrap();
+        let length_config = PKTCTRL0(chip.borrow().register(Config::PKTCTRL0)).length_config();
+
+        cc1101.set_whitening(false).unwrap();
+        let pktctrl0 = PKTCTRL0(chip.borrow().register(Config::PKTCTRL0));
+        assert_eq!(pktctrl0.white_data(), 0);
+        assert_eq!(pktctrl0.length_config(), length_config);
+
+        cc1101.set_whitening(true).unwrap();
+        let pktctrl0 = PKTCTRL0(chip.borrow().register(Config::PKTCTRL0));
+        assert_eq!(pktctrl0.white_data(), 1);
+        assert_eq!(pktctrl0.length_config(), length_config);
+    }
+
+    #[test]
+    fn test_set_manchester() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_modulation(crate::Modulation::OnOffKeying).unwrap();
+        cc1101.set_sync_mode(crate::SyncMode::MatchFull(0xD391)).unwrap();
+
+        cc1101.set_manchester(true).unwrap();
+        let mdmcfg2 = MDMCFG2(chip.borrow().register(Config::MDMCFG2));
+        assert_eq!(mdmcfg2.manchester_en(), 1);
+        assert_eq!(mdmcfg2.mod_format(), ModFormat::MOD_ASK_OOK.value());
+        assert_eq!(mdmcfg2.sync_mode(), SyncCheck::CHECK_16_16.value());
+
+        cc1101.set_manchester(false).unwrap();
+        let mdmcfg2 = MDMCFG2(chip.borrow().register(Config::MDMCFG2));
+        assert_eq!(mdmcfg2.manchester_en(), 0);
+        assert_eq!(mdmcfg2.mod_format(), ModFormat::MOD_ASK_OOK.value());
+    }
+
+    #[test]
+    fn test_set_preamble() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let reset = chip.borrow().register(Config::MDMCFG1);
+        cc1101.set_preamble(crate::PreambleLength::Bytes24).unwrap();
+
+        let mdmcfg1 = chip.borrow().register(Config::MDMCFG1);
+        assert_eq!(MDMCFG1(mdmcfg1).num_preamble(), NumPreamble::N_24.value());
+        assert_eq!(
+            mdmcfg1 & !MDMCFG1::mask().num_preamble(),
+            reset & !MDMCFG1::mask().num_preamble()
+        );
+    }
+
+    #[test]
+    fn test_receive_appended_status() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_append_status(true).unwrap();
+        chip.borrow_mut().rx_fifo.extend([4, 0x42, 1, 2, 3, 0x20, 0x95]);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 8];
+        let packet = cc1101.receive(&mut addr, &mut buf).unwrap();
+
+        assert_eq!(addr, 0x42);
+        assert_eq!(buf[..3], [1, 2, 3]);
+        assert_eq!(packet.len, 4);
+        assert_eq!(packet.rssi_dbm, -58);
+        assert_eq!(packet.lqi, 0x15);
+        assert!(packet.crc_ok);
+    }
+
+    #[test]
+    fn test_try_receive() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut addr = 0;
+        let mut buf = [0u8; 8];
+        assert!(cc1101.try_receive(&mut addr, &mut buf).unwrap().is_none());
+
+        // Sync word seen, packet still arriving.
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x95]);
+        chip.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0x08;
+        assert!(cc1101.try_receive(&mut addr, &mut buf).unwrap().is_none());
+
+        chip.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0x00;
+        let packet = cc1101.try_receive(&mut addr, &mut buf).unwrap().unwrap();
+        assert_eq!(packet.len, 3);
+        assert_eq!(addr, 0x42);
+        assert_eq!(buf[..2], [1, 2]);
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn test_transmit_variable_length() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_packet_length(crate::PacketLength::Variable(8)).unwrap();
+        assert_eq!(cc1101.transmit(&[0x42, 1, 2], &mut mock::Delay).unwrap(), 4);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.tx_fifo, [3, 0x42, 1, 2]);
+        assert_eq!(chip.marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_transmit_fixed_length() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_packet_length(crate::PacketLength::Fixed(3)).unwrap();
+        let result = cc1101.transmit(&[1, 2], &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                expected: crate::PacketLength::Fixed(3),
+                actual: 2
+            })
+        ));
+
+        assert_eq!(cc1101.transmit(&[1, 2, 3], &mut mock::Delay).unwrap(), 3);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.tx_fifo, [1, 2, 3]);
+        assert_eq!(chip.marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_transmit_stay_in_tx() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_txoff_mode(crate::RadioMode::Transmit).unwrap();
+        assert_eq!(cc1101.transmit(&[0x42], &mut mock::Delay).unwrap(), 2);
+        assert_eq!(chip.borrow().sent, [0x01, 0x42]);
+        assert_eq!(chip.borrow().marcstate, MachineState::TX.value());
+    }
+
+    #[test]
+    fn test_transmit_timeout() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        // The synthesizer never locks, so the radio never enters TX.
+        chip.borrow_mut().pll_unlocked = true;
+        let result = cc1101.transmit(&[0x42], &mut mock::Delay);
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_transmit_stream() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut data = [0u8; 300];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        cc1101.transmit_stream(&data).unwrap();
+
+        let chip = chip.borrow();
+        let mut sent = chip.sent.clone();
+        sent.extend(&chip.tx_fifo);
+        assert_eq!(sent, data);
+        assert_eq!(chip.register(Config::PKTCTRL0), 0x45);
+        assert_eq!(chip.register(Config::PKTLEN), 0xFF);
+        assert_eq!(chip.register(Config::IOCFG0), 0x3F);
+    }
+
+    #[test]
+    fn test_receive_stream() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut data = [0u8; 200];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        chip.borrow_mut().rx_air.extend(data);
+        chip.borrow_mut().gdo[0] = true;
+
+        let mut out = [0u8; 200];
+        cc1101.receive_stream(&mut out).unwrap();
+
+        assert_eq!(out, data);
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::PKTCTRL0), 0x45);
+        assert_eq!(chip.register(Config::PKTLEN), 0xFF);
+        assert_eq!(chip.register(Config::IOCFG0), 0x3F);
+    }
+
+    #[test]
+    fn test_last_status() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+        assert!(cc1101.last_status().is_none());
+
+        chip.borrow_mut().rx_fifo.extend([1, 2, 3]);
+        cc1101.set_radio_mode(crate::RadioMode::Receive).unwrap();
+
+        let status = cc1101.last_status().unwrap();
+        assert!(status.chip_ready);
+        assert_eq!(status.state, crate::ChipState::Receive);
+        assert_eq!(status.fifo_bytes_available, 3);
+    }
+
+    #[test]
+    fn test_get_machine_state() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert_eq!(cc1101.get_machine_state().unwrap(), MachineState::IDLE);
+
+        chip.borrow_mut().marcstate = MachineState::RXFIFO_OVERFLOW.value();
+        assert_eq!(cc1101.get_machine_state().unwrap(), MachineState::RXFIFO_OVERFLOW);
+
+        chip.borrow_mut().marcstate = 0x1F;
+        let result = cc1101.get_machine_state();
+        assert!(matches!(result, Err(crate::Error::UnknownMachineState(0x1F))));
+    }
+
+    #[test]
+    fn test_dump_load_config() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut cfg = [0u8; 47];
+        cc1101.dump_config(&mut cfg).unwrap();
+        assert_eq!(cfg, chip.borrow().config);
+
+        cfg[Config::CHANNR.addr() as usize] = 0x11;
+        cfg[Config::TEST0.addr() as usize] = 0x09;
+        cc1101.load_config(&cfg).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.config, cfg);
+        assert_eq!(chip.transactions.len(), 2);
+        assert_eq!(chip.transactions[1][0], 0x40);
+    }
+
+    #[test]
+    fn test_pll_unlocked() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_radio_mode(crate::RadioMode::Receive).unwrap();
+        assert!(cc1101.is_pll_locked().unwrap());
+
+        chip.borrow_mut().pll_unlocked = true;
+        let result = cc1101.set_radio_mode(crate::RadioMode::Transmit);
+        assert!(matches!(result, Err(crate::Error::PllUnlocked)));
+        assert!(!cc1101.is_pll_locked().unwrap());
+    }
+
+    #[test]
+    fn test_set_frequency_out_of_range() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_frequency(433_920_000).unwrap();
+        cc1101.set_frequency(779_000_000).unwrap();
+        for hz in [299_999_999, 350_000_000, 464_000_001, 500_000_000, 928_000_001] {
+            let result = cc1101.set_frequency(hz);
+            assert!(matches!(result, Err(crate::Error::FrequencyOutOfRange)));
+        }
+        assert_eq!(chip.borrow().register(Config::FREQ2), 0x1D);
+    }
+
+    #[test]
+    fn test_modem_getters() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        // Reset values
+        assert_eq!(cc1101.get_data_rate().unwrap(), 115051);
+        assert_eq!(cc1101.get_deviation().unwrap(), 47607);
+        assert_eq!(cc1101.get_chanbw().unwrap(), 203125);
+
+        cc1101.set_data_rate(38400).unwrap();
+        cc1101.set_deviation(20_000).unwrap();
+        cc1101.set_chanbw(101_562).unwrap();
+        // Within one LSB, ~99 baud and ~1587 Hz at these exponents.
+        assert!(cc1101.get_data_rate().unwrap().abs_diff(38400) <= 99);
+        assert!(cc1101.get_deviation().unwrap().abs_diff(20_000) <= 1587);
+        assert_eq!(cc1101.get_chanbw().unwrap(), 101_562);
+    }
+
+    #[test]
+    fn test_set_if_frequency() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_if_frequency(304_687).unwrap();
+        assert_eq!(chip.borrow().register(Config::FSCTRL1), 0x0C);
+        assert_eq!(cc1101.get_if_frequency().unwrap(), 304_687);
+
+        let result = cc1101.set_if_frequency(800_000);
+        assert!(matches!(result, Err(crate::Error::IfFrequencyOutOfRange)));
+    }
+
+    #[test]
+    fn test_set_freq_offset_correction() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_freq_offset_correction(-4760).unwrap();
+        assert_eq!(chip.borrow().register(Config::FSCTRL0), 0xFD);
+        assert_eq!(cc1101.get_freq_offset_correction().unwrap(), -4760);
+
+        let result = cc1101.set_freq_offset_correction(250_000);
+        assert!(matches!(result, Err(crate::Error::FreqOffsetOutOfRange)));
+    }
+
+    #[test]
+    fn test_enable_clock_output() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        // Drive strength and inversion bits are preserved.
+        chip.borrow_mut().config[Config::IOCFG1.addr() as usize] = 0xEE;
+        cc1101.enable_clock_output(crate::GdoPin::Gdo1, crate::ClockDivider::Div1_5).unwrap();
+        cc1101.enable_clock_output(crate::GdoPin::Gdo2, crate::ClockDivider::Div192).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::IOCFG1), 0xF1);
+        assert_eq!(chip.register(Config::IOCFG2), 0x3F);
+    }
+
+    #[test]
+    fn test_measure_channel_rssi() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().status[(Status::RSSI.addr() - 0x30) as usize] = 0x20;
+        assert_eq!(cc1101.measure_channel_rssi(&mut mock::Delay).unwrap(), -58);
+        assert_eq!(chip.borrow().marcstate, MachineState::RX.value());
+    }
+
+    #[test]
+    fn test_transmit_if_clear() {
+        /// Raises carrier sense once the RSSI settle time has passed.
+        struct CarrierAfter(mock::Handle, u32);
+
+        impl hal::delay::DelayNs for CarrierAfter {
+            fn delay_ns(&mut self, ns: u32) {
+                self.1 += ns / 1000;
+                if self.1 >= crate::RSSI_SETTLE_US {
+                    self.0.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0x40;
+                }
+            }
+        }
+
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert_eq!(cc1101.transmit_if_clear(&[0x42], &mut mock::Delay).unwrap(), 2);
+        assert_eq!(chip.borrow().tx_fifo, [0x01, 0x42]);
+
+        chip.borrow_mut().transactions.clear();
+        let result = cc1101.transmit_if_clear(&[0x42], &mut CarrierAfter(chip.clone(), 0));
+        assert!(matches!(result, Err(crate::Error::ChannelBusy)));
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+        assert!(!chip.borrow().transactions.iter().any(|t| t[0] == 0x35));
+    }
+
+    #[test]
+    fn test_scan_channels() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_channel(7).unwrap();
+        chip.borrow_mut().status[(Status::RSSI.addr() - 0x30) as usize] = 0x20;
+        let mut out = [0i16; 4];
+        assert_eq!(cc1101.scan_channels(10..13, &mut mock::Delay, &mut out).unwrap(), 3);
+        assert_eq!(out, [-58, -58, -58, 0]);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::CHANNR), 7);
+        assert_eq!(chip.marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_get_pkt_status() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0b1010_1001;
+        let status = cc1101.get_pkt_status().unwrap();
+        assert!(status.crc_ok);
+        assert!(!status.carrier_sense);
+        assert!(status.pqt_reached);
+        assert!(!status.channel_clear);
+        assert!(status.sync_found);
+        assert!(!status.gdo2);
+        assert!(status.gdo0);
+    }
+
+    #[test]
+    fn test_set_dc_filter() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_manchester(true).unwrap();
+        cc1101.set_dc_filter(false).unwrap();
+        assert_eq!(chip.borrow().register(Config::MDMCFG2), 0x8A);
+
+        cc1101.set_dc_filter(true).unwrap();
+        assert_eq!(chip.borrow().register(Config::MDMCFG2), 0x0A);
+    }
+
+    #[test]
+    fn test_set_foc_config() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_foc_config(crate::FocConfig::default()).unwrap();
+        assert_eq!(chip.borrow().register(Config::FOCCFG), 0x36);
+
+        let cfg = crate::FocConfig::default().cs_gate(false).pre_k(3).post_k(0).limit(0);
+        cc1101.set_foc_config(cfg).unwrap();
+        assert_eq!(chip.borrow().register(Config::FOCCFG), 0x18);
+
+        let result = cc1101.set_foc_config(crate::FocConfig::default().post_k(2));
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        let result = cc1101.set_foc_config(crate::FocConfig::default().limit(4));
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::FOCCFG), 0x18);
+    }
+
+    #[test]
+    fn test_set_bit_sync_config() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_bit_sync_config(crate::BsConfig::default()).unwrap();
+        assert_eq!(chip.borrow().register(Config::BSCFG), 0x6C);
+
+        cc1101.set_bit_sync_config(crate::BsConfig::default().limit(3)).unwrap();
+        assert_eq!(chip.borrow().register(Config::BSCFG), 0x6F);
+
+        let result = cc1101.set_bit_sync_config(crate::BsConfig::default().post_kp(2));
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        let result = cc1101.set_bit_sync_config(crate::BsConfig::default().pre_ki(4));
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::BSCFG), 0x6F);
+    }
+
+    #[test]
+    fn test_flush_rx() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().rx_fifo.extend([1, 2, 3]);
+        cc1101.set_radio_mode(crate::RadioMode::Receive).unwrap();
+        cc1101.flush_rx().unwrap();
+        assert!(chip.borrow().rx_fifo.is_empty());
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+
+        // An overflowed FIFO is flushed directly, which returns the radio to IDLE.
+        chip.borrow_mut().marcstate = MachineState::RXFIFO_OVERFLOW.value();
+        chip.borrow_mut().transactions.clear();
+        cc1101.flush_rx().unwrap();
+        assert_eq!(chip.borrow().transactions.len(), 2);
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_receive_exact_length() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut addr = 0;
+        let mut buf = [0xAAu8; 8];
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x95]);
+        cc1101.receive(&mut addr, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA]);
+
+        let mut buf = [0u8; 2];
+        chip.borrow_mut().rx_fifo.extend([4, 0x42, 1, 2, 3, 0x20, 0x95]);
+        let result = cc1101.receive(&mut addr, &mut buf);
+        assert!(matches!(result, Err(crate::Error::RxOverflow)));
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn test_register_raw() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.write_register_raw(0x0A, 0x07).unwrap();
+        assert_eq!(cc1101.read_register_raw(0x0A).unwrap(), 0x07);
+        chip.borrow_mut().status[0x01] = 0x14;
+        assert_eq!(cc1101.read_register_raw(0x31).unwrap(), 0x14);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::CHANNR), 0x07);
+        assert_eq!(chip.transactions[0], [0x0A, 0x07]);
+        assert_eq!(chip.transactions[1], [0x8A, 0x00]);
+        assert_eq!(chip.transactions[2], [0xF1, 0x00]);
+    }
+
+    #[test]
+    fn test_read_status_registers() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().status[..5].copy_from_slice(&[0x00, 0x14, 0x00, 0xAA, 0x20]);
+        chip.borrow_mut().rx_fifo.extend([1, 2, 3]);
+        let status = cc1101.read_status_registers().unwrap();
+
+        assert_eq!(status.version, 0x14);
+        assert!(status.crc_ok);
+        assert_eq!(status.lqi, 0x2A);
+        assert_eq!(status.rssi_dbm, -58);
+        assert_eq!(status.machine_state, MachineState::IDLE);
+        assert_eq!(status.rx_bytes, 3);
+        assert!(!status.rx_overflow);
+        assert_eq!(status.tx_bytes, 0);
+
+        // One single-byte read per register, with the burst bit set.
+        let chip = chip.borrow();
+        assert_eq!(chip.transactions.len(), 12);
+        assert!(chip.transactions.iter().all(|t| t.len() == 2 && t[0] & 0xF0 == 0xF0));
+    }
+
+    #[test]
+    fn test_tx_bytes_available() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().tx_fifo.extend([1, 2, 3]);
+        assert_eq!(cc1101.tx_bytes_available().unwrap(), 3);
+
+        chip.borrow_mut().marcstate = MachineState::TXFIFO_UNDERFLOW.value();
+        assert!(matches!(cc1101.tx_bytes_available(), Err(crate::Error::TxUnderflow)));
+        assert!(chip.borrow().tx_fifo.is_empty());
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_error_display() {
+        use core::fmt::Write;
 
-/// Address check configuration.
-pub enum AddressFilter {
-    /// No address check.
-    Disabled,
-    /// Address check, no broadcast.
-    Device(u8),
-    /// Address check and 0 (0x00) broadcast.
-    DeviceLowBroadcast(u8),
-    /// Address check and 0 (0x00) and 255 (0xFF) broadcast.
-    DeviceHighLowBroadcast(u8),
-}
+        let mut out = heapless::String::<32>::new();
+        write!(out, "{}", crate::Error::<&str, &str>::RxOverflow).unwrap();
+        assert_eq!(out, "RX FIFO overflow");
 
-/// Radio operational mode.
-pub enum RadioMode {
-    Receive,
-    Transmit,
-    Idle,
-}
+        out.clear();
+        write!(out, "{}", crate::Error::<&str, &str>::UnknownMachineState(0x1F)).unwrap();
+        assert_eq!(out, "unknown machine state 0x1f");
 
-/// Sync word configuration.
-pub enum SyncMode {
-    /// No sync word.
-    Disabled,
-    /// Match 15 of 16 bits of given sync word.
-    MatchPartial(u16),
-    /// Match 30 of 32 bits of a repetition of given sync word.
-    MatchPartialRepeated(u16),
-    /// Match 16 of 16 bits of given sync word.
-    MatchFull(u16),
+        out.clear();
+        write!(out, "{}", crate::Error::<&str, &str>::from(crate::lowlevel::Error::Spi("bus")))
+            .unwrap();
+        assert_eq!(out, "SPI error: bus");
+    }
+
+    #[test]
+    fn test_receive_on_interrupt() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.enable_packet_interrupt(crate::GdoPin::Gdo2).unwrap();
+        assert_eq!(chip.borrow().register(Config::IOCFG2), 0x06);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        assert!(cc1101.receive_on_interrupt(&mut addr, &mut buf).unwrap().is_none());
+
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x95]);
+        let packet = cc1101.receive_on_interrupt(&mut addr, &mut buf).unwrap().unwrap();
+        assert_eq!(packet.len, 3);
+        assert_eq!(buf[..2], [1, 2]);
+    }
+
+    #[test]
+    fn test_apply_default() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let reset = chip.borrow().config;
+        cc1101.apply(&crate::RadioConfig::default()).unwrap();
+
+        assert_eq!(chip.borrow().config, reset);
+    }
+
+    #[test]
+    fn test_apply() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let config = crate::RadioConfig::default()
+            .frequency(433_920_000)
+            .data_rate(38_400)
+            .modulation(crate::Modulation::GaussianFrequencyShiftKeying)
+            .sync_mode(crate::SyncMode::MatchPartial(0x1234))
+            .manchester(true)
+            .dc_filter(false)
+            .packet_length(crate::PacketLength::Fixed(16))
+            .address_filter(crate::AddressFilter::Device(0x42))
+            .whitening(false)
+            .tx_power_dbm(10);
+        cc1101.apply(&config).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::FREQ2), 0x10);
+        assert_eq!(chip.register(Config::FREQ1), 0xB0);
+        assert_eq!(chip.register(Config::FREQ0), 0x71);
+        assert_eq!(chip.register(Config::MDMCFG3), 0x84);
+        assert_eq!(chip.register(Config::MDMCFG2), 0x99);
+        assert_eq!(chip.register(Config::SYNC1), 0x12);
+        assert_eq!(chip.register(Config::SYNC0), 0x34);
+        assert_eq!(chip.register(Config::PKTCTRL0), 0x04);
+        assert_eq!(chip.register(Config::PKTLEN), 16);
+        assert_eq!(chip.register(Config::PKTCTRL1), 0x05);
+        assert_eq!(chip.register(Config::ADDR), 0x42);
+        assert_eq!(chip.patable[0], 0xC0);
+        // MDMCFG2 is written once, never read back and modified.
+        let mdmcfg2 = chip.transactions.iter().filter(|t| t[0] & 0x3F == 0x12).count();
+        assert_eq!(mdmcfg2, 1);
+    }
+
+    #[test]
+    fn test_apply_preset() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.apply(&crate::RadioConfig::preset_433mhz_38k4_gfsk()).unwrap();
+
+        // SmartRF Studio export, 433.92 MHz GFSK 38.4 kBaud.
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::FREQ2), 0x10);
+        assert_eq!(chip.register(Config::FREQ1), 0xB0);
+        assert_eq!(chip.register(Config::FREQ0), 0x71);
+        assert_eq!(chip.register(Config::MDMCFG4), 0xCA);
+        assert_eq!(chip.register(Config::MDMCFG3), 0x83);
+        assert_eq!(chip.register(Config::MDMCFG2), 0x13);
+        assert_eq!(chip.register(Config::DEVIATN), 0x35);
+        assert_eq!(chip.register(Config::PKTCTRL0), 0x05);
+        assert_eq!(chip.register(Config::AGCCTRL2), 0x43);
+        assert_eq!(chip.register(Config::AGCCTRL1), 0x40);
+        assert_eq!(chip.register(Config::AGCCTRL0), 0x91);
+    }
+
+    #[test]
+    fn test_apply_presets_1k2() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.apply(&crate::RadioConfig::preset_868mhz_1k2_gfsk()).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::FREQ2), 0x21);
+        assert_eq!(chip.register(Config::FREQ1), 0x65);
+        assert_eq!(chip.register(Config::FREQ0), 0x6A);
+        assert_eq!(chip.register(Config::MDMCFG4), 0xF5);
+        assert_eq!(chip.register(Config::MDMCFG3), 0x83);
+        assert_eq!(chip.register(Config::DEVIATN), 0x15);
+        assert_eq!(chip.register(Config::AGCCTRL2), 0x03);
+    }
+
+    #[test]
+    fn test_apply_invalid() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let config = crate::RadioConfig::default().frequency(433_920_000).tx_power_dbm(11);
+        let result = cc1101.apply(&config);
+
+        assert!(matches!(result, Err(crate::Error::InvalidPower)));
+        assert!(chip.borrow().transactions.is_empty());
+
+        for config in [
+            crate::RadioConfig::default().data_rate(0),
+            crate::RadioConfig::default().deviation(0),
+            crate::RadioConfig::default().chanbw(0),
+            crate::RadioConfig::default().agc(crate::AgcConfig::default().magn_target(8)),
+        ]
+        .iter()
+        {
+            assert!(matches!(cc1101.apply(config), Err(crate::Error::InvalidConfig)));
+        }
+        assert!(chip.borrow().transactions.is_empty());
+    }
+
+    #[test]
+    fn test_transmit_addressed() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert_eq!(cc1101.transmit_addressed(0x42, &[1, 2, 3], &mut mock::Delay).unwrap(), 5);
+        assert_eq!(chip.borrow().tx_fifo, [4, 0x42, 1, 2, 3]);
+
+        cc1101.set_packet_length(crate::PacketLength::Fixed(3)).unwrap();
+        cc1101.transmit_addressed(0x42, &[1, 2], &mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().tx_fifo, [0x42, 1, 2]);
+
+        let result = cc1101.transmit_addressed(0x42, &[1, 2, 3], &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                actual: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_transmit_broadcast() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.transmit_broadcast(&[1, 2], &mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().tx_fifo, [3, 0x00, 1, 2]);
+    }
+
+    #[test]
+    fn test_transmit_invalid_length() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let payload = [0u8; 64];
+        let result = cc1101.transmit(&payload, &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                expected: crate::PacketLength::Variable(63),
+                actual: 64
+            })
+        ));
+
+        cc1101.set_packet_length(crate::PacketLength::Variable(8)).unwrap();
+        let result = cc1101.transmit(&payload[..9], &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                expected: crate::PacketLength::Variable(8),
+                actual: 9
+            })
+        ));
+
+        let payload = [0u8; crate::MAX_TX + 1];
+        cc1101.set_packet_length(crate::PacketLength::Fixed(65)).unwrap();
+        let result = cc1101.transmit(&payload, &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                actual: 65,
+                ..
+            })
+        ));
+
+        cc1101.set_packet_length(crate::PacketLength::Infinite).unwrap();
+        assert_eq!(cc1101.get_packet_length().unwrap(), crate::PacketLength::Infinite);
+        let result = cc1101.transmit(&payload[..8], &mut mock::Delay);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidLength {
+                expected: crate::PacketLength::Infinite,
+                actual: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_recover() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert!(!cc1101.recover().unwrap());
+
+        chip.borrow_mut().marcstate = MachineState::RXFIFO_OVERFLOW.value();
+        chip.borrow_mut().rx_fifo.extend([1, 2, 3]);
+        assert!(cc1101.recover().unwrap());
+        assert!(chip.borrow().rx_fifo.is_empty());
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+
+        chip.borrow_mut().marcstate = MachineState::TXFIFO_UNDERFLOW.value();
+        chip.borrow_mut().tx_fifo.extend([1, 2, 3]);
+        assert!(cc1101.recover().unwrap());
+        assert!(chip.borrow().tx_fifo.is_empty());
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+    }
+
+    #[test]
+    fn test_set_pqt() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_address_filter(crate::AddressFilter::Device(0x42)).unwrap();
+        cc1101.set_pqt(5).unwrap();
+
+        assert_eq!(chip.borrow().register(Config::PKTCTRL1), 0xA5);
+
+        assert!(matches!(cc1101.set_pqt(8), Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::PKTCTRL1), 0xA5);
+    }
+
+    #[test]
+    fn test_sync_mode_repeated() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert_eq!(crate::SyncMode::repeated(0x1234_5678), None);
+        let sync_mode = crate::SyncMode::repeated(0x1234_1234).unwrap();
+        assert_eq!(sync_mode, crate::SyncMode::MatchPartialRepeated(0x1234));
+
+        cc1101.set_sync_mode(sync_mode).unwrap();
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::SYNC1), 0x12);
+        assert_eq!(chip.register(Config::SYNC0), 0x34);
+        assert_eq!(chip.register(Config::MDMCFG2) & 0x07, 0x03);
+    }
+
+    #[test]
+    fn test_set_radio_mode_fast_ready() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_radio_mode(crate::RadioMode::FastReady).unwrap();
+        assert_eq!(chip.borrow().marcstate, MachineState::FSTXON.value());
+
+        chip.borrow_mut().transactions.clear();
+        cc1101.set_radio_mode(crate::RadioMode::Receive).unwrap();
+        assert_eq!(chip.borrow().marcstate, MachineState::RX.value());
+        // Straight from FSTXON to RX, without SIDLE.
+        assert!(!chip.borrow().transactions.iter().any(|t| t[0] == 0x36));
+
+        cc1101.set_txoff_mode(crate::RadioMode::FastReady).unwrap();
+        cc1101.set_rxoff_mode(crate::RadioMode::FastReady).unwrap();
+        assert_eq!(chip.borrow().register(Config::MCSM1) & 0x0F, 0x05);
+    }
+
+    #[test]
+    fn test_transmit_payload_sizes() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.transmit(&[0x42], &mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().tx_fifo, [1, 0x42]);
+
+        let mut payload = [0u8; 64];
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        cc1101.set_packet_length(crate::PacketLength::Fixed(64)).unwrap();
+        cc1101.transmit(&payload, &mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().tx_fifo, payload);
+    }
+
+    #[test]
+    fn test_4fsk_constraints() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_manchester(true).unwrap();
+        let result = cc1101.set_modulation(crate::Modulation::FourFrequencyShiftKeying);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        cc1101.set_manchester(false).unwrap();
+
+        cc1101.set_data_rate(500_000).unwrap();
+        let result = cc1101.set_modulation(crate::Modulation::FourFrequencyShiftKeying);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        cc1101.set_data_rate(250_000).unwrap();
+
+        cc1101.set_modulation(crate::Modulation::FourFrequencyShiftKeying).unwrap();
+        assert_eq!(chip.borrow().register(Config::MDMCFG2) & 0x70, 0x40);
+        assert!(matches!(cc1101.set_manchester(true), Err(crate::Error::InvalidConfig)));
+        assert!(matches!(cc1101.set_data_rate(500_000), Err(crate::Error::InvalidConfig)));
+
+        let config = crate::RadioConfig::default()
+            .modulation(crate::Modulation::FourFrequencyShiftKeying)
+            .data_rate(500_000);
+        assert!(matches!(cc1101.apply(&config), Err(crate::Error::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_receive_with_filter_info() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_address_filter(crate::AddressFilter::DeviceLowBroadcast(0x42)).unwrap();
+        let pktctrl1 = chip.borrow().register(Config::PKTCTRL1);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        for (sent, expected) in [(0x17, false), (0x42, true), (0x00, true), (0xFF, false)] {
+            chip.borrow_mut().rx_fifo.extend([2, sent, 1, 0x20, 0x95]);
+            let (packet, matched) = cc1101.receive_with_filter_info(&mut addr, &mut buf).unwrap();
+            assert_eq!(addr, sent);
+            assert_eq!(packet.len, 2);
+            assert_eq!(matched, expected);
+        }
+        assert_eq!(chip.borrow().register(Config::PKTCTRL1), pktctrl1);
+    }
+
+    #[test]
+    fn test_set_agcctrl2() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_agc(crate::AgcConfig::default().max_dvga_gain(2)).unwrap();
+        cc1101.set_max_lna_gain(5).unwrap();
+        cc1101.set_magn_target(crate::MagnTarget::DB_40).unwrap();
+
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), 0xAE);
+
+        assert!(matches!(cc1101.set_max_lna_gain(8), Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), 0xAE);
+    }
+
+    #[test]
+    fn test_is_data_available() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert!(!cc1101.is_data_available().unwrap());
+
+        chip.borrow_mut().rx_fifo.extend([2, 0x42, 1, 0x20, 0x95]);
+        chip.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0x08;
+        assert!(!cc1101.is_data_available().unwrap());
+
+        chip.borrow_mut().status[(Status::PKTSTATUS.addr() - 0x30) as usize] = 0x00;
+        assert!(cc1101.is_data_available().unwrap());
+        assert_eq!(chip.borrow().rx_fifo.len(), 5);
+    }
+
+    #[test]
+    fn test_get_link_quality_percent() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().status[(Status::LQI.addr() - 0x30) as usize] = 0x80;
+        assert_eq!(cc1101.get_link_quality_percent().unwrap(), 100);
+        chip.borrow_mut().status[(Status::LQI.addr() - 0x30) as usize] = 0x7F;
+        assert_eq!(cc1101.get_link_quality_percent().unwrap(), 0);
+        chip.borrow_mut().status[(Status::LQI.addr() - 0x30) as usize] = 0x20;
+        assert_eq!(cc1101.get_link_quality_percent().unwrap(), 74);
+    }
+
+    #[test]
+    fn test_last_packet_info() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+        assert_eq!(cc1101.last_packet_info(), None);
+
+        chip.borrow_mut().rx_fifo.extend([2, 0x42, 1, 0x20, 0x15]);
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        assert!(matches!(cc1101.try_receive(&mut addr, &mut buf), Err(crate::Error::CrcMismatch)));
+
+        let info = cc1101.last_packet_info().unwrap();
+        assert_eq!(info.rssi_dbm, -58);
+        assert_eq!(info.lqi, 0x15);
+        assert!(!info.crc_ok);
+    }
+
+    #[test]
+    fn test_release() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let (spi, cs, gdo0, gdo2) = cc1101.release();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+        cc1101.set_sync_mode(crate::SyncMode::MatchFull(0x1234)).unwrap();
+        assert_eq!(chip.borrow().register(Config::SYNC1), 0x12);
+    }
+
+    #[test]
+    fn test_lowlevel() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.lowlevel().write_register(Config::FSCTRL1, 0x06).unwrap();
+        assert_eq!(cc1101.lowlevel().read_register(Config::FSCTRL1).unwrap(), 0x06);
+        assert_eq!(chip.borrow().register(Config::FSCTRL1), 0x06);
+    }
+
+    #[test]
+    fn test_set_close_in_rx() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_fifo_threshold(FifoThreshold::TX_33_RX_32).unwrap();
+        cc1101.set_close_in_rx(crate::CloseInRx::DB_12).unwrap();
+        assert_eq!(chip.borrow().register(Config::FIFOTHR), 0x27);
+    }
+
+    #[test]
+    fn test_set_agc_filter_length() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_agc_filter_length(crate::AgcFilterLength::SAMPLES_64).unwrap();
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), 0x93);
+        let result = cc1101.set_ask_decision_boundary(crate::AskDecisionBoundary::DB_4);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+
+        cc1101.set_modulation(crate::Modulation::OnOffKeying).unwrap();
+        cc1101.set_ask_decision_boundary(crate::AskDecisionBoundary::DB_12).unwrap();
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), 0x92);
+        let result = cc1101.set_agc_filter_length(crate::AgcFilterLength::SAMPLES_8);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_set_pa_power_index() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_pa_power_index(1).unwrap();
+        assert_eq!(chip.borrow().register(Config::FREND0), 0x11);
+
+        assert!(matches!(cc1101.set_pa_power_index(8), Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::FREND0), 0x11);
+    }
+
+    #[test]
+    fn test_set_frend1() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_frend1(crate::Frend1Config::default().lna_current(2)).unwrap();
+        assert_eq!(chip.borrow().register(Config::FREND1), 0x96);
+
+        let result = cc1101.set_frend1(crate::Frend1Config::default().mix_current(4));
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+        assert_eq!(chip.borrow().register(Config::FREND1), 0x96);
+    }
+
+    #[test]
+    fn test_transmit_with_retries() {
+        /// Receives an acknowledgement while listening after the given transmission.
+        struct AckAfter(mock::Handle, usize);
+
+        impl hal::delay::DelayNs for AckAfter {
+            fn delay_ns(&mut self, _ns: u32) {
+                let mut chip = self.0.borrow_mut();
+                if chip.transactions.iter().filter(|t| t[..] == [0x35]).count() == self.1 {
+                    chip.rx_fifo.extend([2, 0x01, 0xAC, 0x20, 0x95]);
+                    self.1 = 0;
+                }
+            }
+        }
+
+        let strobes = |chip: &mock::Handle| {
+            let chip = chip.borrow();
+            chip.transactions.iter().filter(|t| t[..] == [0x35]).count()
+        };
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().rx_fifo.extend([2, 0x01, 0xAC, 0x20, 0x95]);
+        let written = cc1101
+            .transmit_with_retries(&[0x42, 1], 2, 1000, &mut mock::Delay, |addr, payload| {
+                addr == 0x01 && payload == [0xAC]
+            })
+            .unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(strobes(&chip), 1);
+        assert_eq!(chip.borrow().marcstate, MachineState::IDLE.value());
+
+        chip.borrow_mut().transactions.clear();
+        let mut delay = AckAfter(chip.clone(), 2);
+        let written = cc1101
+            .transmit_with_retries(&[0x42, 1], 2, 1000, &mut delay, |addr, payload| {
+                addr == 0x01 && payload == [0xAC]
+            })
+            .unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(strobes(&chip), 2);
+
+        chip.borrow_mut().transactions.clear();
+        let result =
+            cc1101.transmit_with_retries(&[0x42, 1], 2, 1000, &mut mock::Delay, |_, _| true);
+        assert!(matches!(result, Err(crate::Error::NoAck)));
+        assert_eq!(strobes(&chip), 3);
+        assert_eq!(chip.borrow().tx_fifo, [2, 0x42, 1]);
+    }
+
+    #[test]
+    fn test_wait_for_sync_sent() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let result = cc1101.wait_for_sync_sent(10);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+
+        cc1101.enable_packet_interrupt(crate::GdoPin::Gdo2).unwrap();
+        let result = cc1101.wait_for_sync_sent(10);
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+
+        cc1101.set_gdo2_invert(true).unwrap();
+        cc1101.wait_for_sync_sent(10).unwrap();
+    }
+
+    #[test]
+    fn test_receive_allow_crc_error() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().rx_fifo.extend([3, 0x42, 1, 2, 0x20, 0x15]);
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        let packet = cc1101.receive_allow_crc_error(&mut addr, &mut buf).unwrap();
+
+        assert!(!packet.crc_ok);
+        assert_eq!(packet.len, 3);
+        assert_eq!(addr, 0x42);
+        assert_eq!(buf[..2], [1, 2]);
+    }
+
+    #[test]
+    fn test_set_sensitivity_mode() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_sensitivity_mode(crate::SensitivityMode::HighSensitivity).unwrap();
+        assert_eq!(chip.borrow().register(Config::FREND1), 0xB6);
+        assert_eq!(MDMCFG2(chip.borrow().register(Config::MDMCFG2)).dem_dcfilt_off(), 0);
+
+        cc1101.set_sensitivity_mode(crate::SensitivityMode::LowCurrent).unwrap();
+        assert_eq!(chip.borrow().register(Config::FREND1), 0x56);
+        assert_eq!(MDMCFG2(chip.borrow().register(Config::MDMCFG2)).dem_dcfilt_off(), 1);
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), 0x03);
+
+        cc1101.set_data_rate(500_000).unwrap();
+        let result = cc1101.set_sensitivity_mode(crate::SensitivityMode::LowCurrent);
+        assert!(matches!(result, Err(crate::Error::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_get_wor_time() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let wortime1 = (Status::WORTIME1.addr() - 0x30) as usize;
+        chip.borrow_mut().status[wortime1..wortime1 + 2].copy_from_slice(&[0x0D, 0x8B]);
+        assert_eq!(cc1101.get_wor_time().unwrap(), 3467);
+        assert_eq!(cc1101.get_wor_time_ms().unwrap(), 100);
+
+        // WORTIME1, WORTIME0 and WORTIME1 again, as single reads.
+        assert_eq!(chip.borrow().transactions[..3], [[0xF6, 0], [0xF7, 0], [0xF6, 0]]);
+
+        cc1101.configure_wor(100, 10_000).unwrap();
+        assert_eq!(cc1101.get_wor_time_ms().unwrap(), 3200);
+    }
+
+    #[test]
+    fn test_calibrate_rc_osc() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.write_register_raw(Config::WORCTRL.addr(), 0xF0).unwrap();
+        cc1101.calibrate_rc_osc(&mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().register(Config::WORCTRL), 0x78);
+
+        cc1101.write_register_raw(Config::WORCTRL.addr(), 0xF0).unwrap();
+        cc1101.configure_wor(10, 1000).unwrap();
+        assert_eq!(chip.borrow().register(Config::WORCTRL), 0x78);
+    }
+
+    #[test]
+    fn test_band() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+        assert_eq!(cc1101.current_band().unwrap(), crate::Band::Band868);
+
+        cc1101.set_frequency(433_920_000).unwrap();
+        assert_eq!(cc1101.current_band().unwrap(), crate::Band::Band433);
+        cc1101.set_tx_power_dbm(0).unwrap();
+        assert_eq!(chip.borrow().patable[0], 0x60);
+
+        cc1101.set_band(crate::Band::Band915);
+        assert_eq!(cc1101.current_band().unwrap(), crate::Band::Band915);
+        cc1101.set_tx_power_dbm(0).unwrap();
+        assert_eq!(chip.borrow().patable[0], 0x8E);
+
+        assert_eq!(crate::Band::from_frequency(348_000_000), crate::Band::Band315);
+        assert_eq!(crate::Band::from_frequency(367_499_999), crate::Band::Band315);
+        assert_eq!(crate::Band::from_frequency(367_500_000), crate::Band::Band433);
+        assert_eq!(crate::Band::from_frequency(621_499_999), crate::Band::Band433);
+        assert_eq!(crate::Band::from_frequency(621_500_000), crate::Band::Band868);
+        assert_eq!(crate::Band::from_frequency(900_000_000), crate::Band::Band868);
+        assert_eq!(crate::Band::from_frequency(900_000_001), crate::Band::Band915);
+    }
+
+    #[test]
+    fn test_read_temperature() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+        let iocfg0 = chip.borrow().register(Config::IOCFG0);
+
+        let sampled = chip.clone();
+        let celsius = cc1101
+            .read_temperature(move || {
+                let chip = sampled.borrow();
+                assert_eq!(chip.register(Config::PTEST), super::PTEST_TEMP_SENSOR);
+                assert_eq!(IOCFG0(chip.register(Config::IOCFG0)).temp_sensor_enable(), 1);
+                747
+            })
+            .unwrap();
+        assert_eq!(celsius, 0);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::PTEST), PTEST::default().bits());
+        assert_eq!(chip.register(Config::IOCFG0), iocfg0);
+    }
+
+    #[test]
+    fn test_wake() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().not_ready = 5;
+        cc1101.wake().unwrap();
+        assert!(cc1101.last_status().unwrap().chip_ready);
+
+        chip.borrow_mut().not_ready = u32::MAX;
+        assert!(matches!(cc1101.wake(), Err(crate::Error::Timeout)));
+
+        chip.borrow_mut().not_ready = 0;
+        chip.borrow_mut().marcstate = 0x00;
+        assert!(matches!(cc1101.wake(), Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_reset() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_channel(7).unwrap();
+        chip.borrow_mut().transactions.clear();
+        chip.borrow_mut().not_ready = 3;
+        cc1101.reset(&mut mock::Delay).unwrap();
+        assert_eq!(chip.borrow().register(Config::CHANNR), 0x00);
+
+        {
+            let chip = chip.borrow();
+            // CS strobe, SNOP until ready, then SRES.
+            assert!(chip.transactions[0].is_empty());
+            assert!(chip.transactions[1..4].iter().all(|t| t == &[0x3D]));
+            assert_eq!(chip.transactions[4], [0x30]);
+        }
+
+        chip.borrow_mut().transactions.clear();
+        chip.borrow_mut().not_ready = u32::MAX;
+        assert!(matches!(cc1101.reset(&mut mock::Delay), Err(crate::Error::Timeout)));
+        assert!(!chip.borrow().transactions.iter().any(|t| t == &[0x30]));
+    }
+
+    #[test]
+    fn test_set_test_registers() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_test_registers(0x09, 0x35, 0x81).unwrap();
+        assert_eq!(chip.borrow().register(Config::TEST2), 0x81);
+        assert_eq!(chip.borrow().register(Config::TEST1), 0x35);
+        assert_eq!(chip.borrow().register(Config::TEST0), 0x09);
+    }
+
+    #[test]
+    fn test_set_gdo_config() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_gdo0_config(crate::GdoCfg::SYNC_WORD).unwrap();
+        cc1101.set_gdo1_config(crate::GdoCfg::CHIP_RDYn).unwrap();
+        cc1101.set_gdo2_config(crate::GdoCfg::RX_FIFO_FILLED_END_OF_PKT).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Config::IOCFG0), 0x06);
+        assert_eq!(chip.register(Config::IOCFG1), 0x29);
+        assert_eq!(chip.register(Config::IOCFG2), 0x01);
+    }
+
+    #[test]
+    fn test_set_gdo_invert() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_gdo0_invert(true).unwrap();
+        cc1101.set_gdo1_invert(true).unwrap();
+        cc1101.set_gdo2_invert(true).unwrap();
+        cc1101.set_gdo2_config(crate::GdoCfg::SYNC_WORD).unwrap();
+        assert_eq!(chip.borrow().register(Config::IOCFG0), 0x7F);
+        assert_eq!(chip.borrow().register(Config::IOCFG1), 0x6E);
+        assert_eq!(chip.borrow().register(Config::IOCFG2), 0x46);
+
+        cc1101.set_gdo2_invert(false).unwrap();
+        assert_eq!(chip.borrow().register(Config::IOCFG2), 0x06);
+    }
+
+    #[test]
+    fn test_poll_until_idle() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.poll_until_idle(1).unwrap();
+
+        chip.borrow_mut().marcstate = MachineState::RX.value();
+        assert!(matches!(cc1101.poll_until_idle(10), Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_receive_all() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        chip.borrow_mut().rx_fifo.extend([2, 0x42, 1, 0x20, 0x95]);
+        chip.borrow_mut().rx_fifo.extend([2, 0x43, 2, 0x20, 0x15]);
+        chip.borrow_mut().rx_fifo.extend([3, 0x44, 3, 4, 0x30, 0x90]);
+
+        let mut packets = heapless::Vec::<(u8, u8), 4>::new();
+        let count = cc1101.receive_all(|addr, payload| {
+            packets.push((addr, payload.len() as u8)).unwrap();
+        });
+        assert_eq!(count.unwrap(), 2);
+        assert_eq!(packets, [(0x42, 1), (0x44, 2)]);
+        assert_eq!(cc1101.last_packet_info().unwrap().lqi, 0x10);
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn test_receive_all_partial() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        // A whole packet, followed by the start of one still being received.
+        chip.borrow_mut().rx_fifo.extend([2, 0x42, 1, 0x20, 0x95]);
+        chip.borrow_mut().rx_fifo.extend([3, 0x44]);
+
+        let mut packets = heapless::Vec::<(u8, u8), 4>::new();
+        let count = cc1101.receive_all(|addr, payload| {
+            packets.push((addr, payload.len() as u8)).unwrap();
+        });
+        assert_eq!(count.unwrap(), 1);
+        assert_eq!(packets, [(0x42, 1)]);
+
+        chip.borrow_mut().rx_fifo.extend([3, 4, 0x30, 0x90]);
+        let count = cc1101.receive_all(|addr, payload| {
+            packets.push((addr, payload.len() as u8)).unwrap();
+        });
+        assert_eq!(count.unwrap(), 1);
+        assert_eq!(packets, [(0x42, 1), (0x44, 2)]);
+        assert!(chip.borrow().rx_fifo.is_empty());
+
+        chip.borrow_mut().rx_fifo.extend([0x40, 0x42, 1]);
+        let result = cc1101.receive_all(|_, _| {});
+        assert!(matches!(result, Err(crate::Error::InvalidPacketLength)));
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn test_enter_state() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        assert_eq!(cc1101.enter_rx().unwrap(), MachineState::RX);
+        assert_eq!(cc1101.enter_idle().unwrap(), MachineState::IDLE);
+
+        // TX ends, per TXOFF_MODE, by the time the state is observed.
+        assert_eq!(cc1101.enter_tx().unwrap(), MachineState::IDLE);
+        cc1101.set_txoff_mode(crate::RadioMode::Receive).unwrap();
+        assert_eq!(cc1101.enter_tx().unwrap(), MachineState::RX);
+    }
+
+    #[test]
+    fn test_new_checked() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let result = crate::Cc1101::new_checked(spi, cs, gdo0, gdo2);
+        assert!(matches!(
+            result,
+            Err(crate::Error::UnsupportedChip {
+                partnum: 0x00,
+                version: 0x00
+            })
+        ));
+
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        chip.borrow_mut().status[(Status::VERSION.addr() - 0x30) as usize] = 0x14;
+        assert!(crate::Cc1101::new_checked(spi, cs, gdo0, gdo2).is_ok());
+    }
+
+    #[test]
+    fn test_set_rx_continuous() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_rx_continuous(true).unwrap();
+        assert_eq!(MCSM1(chip.borrow().register(Config::MCSM1)).rxoff_mode(), 0b11);
+        cc1101.set_radio_mode(crate::RadioMode::Receive).unwrap();
+        chip.borrow_mut().rx_fifo.extend([2, 0x42, 1, 0x20, 0x95, 2, 0x43, 2, 0x20, 0x95]);
+
+        let mut addr = 0;
+        let mut buf = [0u8; 4];
+        cc1101.receive(&mut addr, &mut buf).unwrap();
+        assert_eq!((addr, buf[0]), (0x42, 1));
+        assert_eq!(chip.borrow().marcstate, MachineState::RX.value());
+
+        cc1101.receive(&mut addr, &mut buf).unwrap();
+        assert_eq!((addr, buf[0]), (0x43, 2));
+
+        cc1101.set_rx_continuous(false).unwrap();
+        assert_eq!(MCSM1(chip.borrow().register(Config::MCSM1)).rxoff_mode(), 0b00);
+    }
+
+    #[test]
+    fn test_set_agc() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_agc(crate::AgcConfig::default()).unwrap();
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), AGCCTRL2::default().bits());
+        assert_eq!(chip.borrow().register(Config::AGCCTRL1), AGCCTRL1::default().bits());
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), AGCCTRL0::default().bits());
+
+        let agc = crate::AgcConfig::default()
+            .max_lna_gain(2)
+            .magn_target(7)
+            .carrier_sense_abs_thr(-8)
+            .freeze(3);
+        cc1101.set_agc(agc).unwrap();
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), 0x17);
+        assert_eq!(chip.borrow().register(Config::AGCCTRL1), 0x48);
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), 0x9D);
+
+        for agc in [
+            crate::AgcConfig::default().max_dvga_gain(4),
+            crate::AgcConfig::default().max_lna_gain(8),
+            crate::AgcConfig::default().carrier_sense_abs_thr(8),
+            crate::AgcConfig::default().carrier_sense_abs_thr(-9),
+            crate::AgcConfig::default().filter_length(4),
+        ]
+        .iter()
+        {
+            assert!(matches!(cc1101.set_agc(*agc), Err(crate::Error::InvalidConfig)));
+        }
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), 0x9D);
+    }
+
+    #[test]
+    fn test_configure_ook_rx() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.configure_ook_rx().unwrap();
+        assert_eq!(chip.borrow().register(Config::AGCCTRL2), 0x03);
+        assert_eq!(chip.borrow().register(Config::AGCCTRL1), 0x00);
+        assert_eq!(chip.borrow().register(Config::AGCCTRL0), 0x91);
+    }
+
+    #[test]
+    fn test_set_radio_mode_timeout() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = crate::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.set_radio_mode_timeout(crate::RadioMode::Receive, 10).unwrap();
+        assert_eq!(chip.borrow().marcstate, MachineState::RX.value());
+
+        // Nothing arrives in the RX FIFO.
+        let mut addr = 0;
+        let mut buf = [0; 8];
+        let result = cc1101.receive_timeout(&mut addr, &mut buf, 10);
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+    }
 }
@@ -1,4 +1,9 @@
 use crate::lowlevel::FXOSC;
+use core::ops::RangeInclusive;
+
+/// Carrier frequency bands the synthesizer supports, in Hz.
+pub const FREQUENCY_BANDS: [RangeInclusive<u64>; 3] =
+    [300_000_000..=348_000_000, 387_000_000..=464_000_000, 779_000_000..=928_000_000];
 
 pub const fn from_frequency(hz: u64) -> (u8, u8, u8) {
     let freq = hz * 1u64.rotate_left(16) / FXOSC;
@@ -8,12 +13,32 @@ pub const fn from_frequency(hz: u64) -> (u8, u8, u8) {
     (freq0, freq1, freq2)
 }
 
+pub const fn to_frequency(freq0: u8, freq1: u8, freq2: u8) -> u64 {
+    let freq = ((freq2 as u64) << 16) | ((freq1 as u64) << 8) | freq0 as u64;
+    (freq * FXOSC) >> 16
+}
+
+/// Smallest frequency deviation representable by DEVIATN, in Hz, rounded up.
+pub const DEVIATION_MIN: u64 = (FXOSC * 8).div_ceil(1 << 17);
+/// Largest frequency deviation representable by DEVIATN, in Hz.
+pub const DEVIATION_MAX: u64 = FXOSC * 15 * (1 << 7) / (1 << 17);
+
 pub const fn from_deviation(v: u64) -> (u8, u8) {
     let exponent = 64 - (v.rotate_left(14) / FXOSC).leading_zeros() - 1;
     let mantissa = (v.rotate_left(17) / (FXOSC.rotate_left(exponent))) - 7;
     ((mantissa & 0x7) as u8, (exponent & 0x7) as u8)
 }
 
+// f_dev = f_osc / 2^17 * (8 + DEVIATION_M) * 2^DEVIATION_E
+pub const fn to_deviation(mantissa: u8, exponent: u8) -> u64 {
+    (FXOSC * (8 + mantissa as u64)) << exponent >> 17
+}
+
+/// Lowest data rate supported by the modem, in baud.
+pub const DATA_RATE_MIN: u64 = 600;
+/// Highest data rate supported by the modem, in baud.
+pub const DATA_RATE_MAX: u64 = 600_000;
+
 // TODO: Not defined for all values, need to figure out.
 pub const fn from_drate(v: u64) -> (u8, u8) {
     let exponent = 64 - (v.rotate_left(19) / FXOSC).leading_zeros();
@@ -22,12 +47,115 @@ pub const fn from_drate(v: u64) -> (u8, u8) {
     [(mantissa as u8, exponent as u8), (0u8, (exponent + 1) as u8)][(mantissa == 256) as usize]
 }
 
+/// Highest data rate supported with 4-FSK modulation, in baud.
+pub const FSK4_DATA_RATE_MAX: u64 = 300_000;
+
+// R_data = (256 + DRATE_M) * 2^DRATE_E / 2^28 * f_osc
+pub const fn to_drate(mantissa: u8, exponent: u8) -> u64 {
+    ((256 + mantissa as u64) * FXOSC) << exponent >> 28
+}
+
+/// Narrowest channel filter bandwidth representable by MDMCFG4, in Hz.
+pub const CHANBW_MIN: u64 = FXOSC / (8 * 7 * (1 << 3));
+/// Widest channel filter bandwidth representable by MDMCFG4, in Hz.
+pub const CHANBW_MAX: u64 = FXOSC / (8 * 4);
+
 pub fn from_chanbw(v: u64) -> (u8, u8) {
     let exponent = 64 - (FXOSC / (8 * 4 * v)).leading_zeros() - 1;
     let mantissa = FXOSC / (v * 8 * 2u64.pow(exponent)) - 4;
     (mantissa as u8 & 0x3, exponent as u8 & 0x3)
 }
 
+// BW_channel = f_osc / (8 * (4 + CHANBW_M) * 2^CHANBW_E)
+pub const fn to_chanbw(mantissa: u8, exponent: u8) -> u64 {
+    FXOSC / ((8 * (4 + mantissa as u64)) << exponent)
+}
+
+/// Minimum channel filter bandwidth, in Hz, for a data rate and frequency deviation, by
+/// Carson's rule. Add the crystal frequency error of both ends to it for a real link.
+// BW ≈ 2 * f_dev + R_data
+pub const fn min_channel_bandwidth(baud: u64, deviation: u64) -> u64 {
+    2 * deviation + baud
+}
+
+/// Largest IF frequency representable by FSCTRL1, in Hz.
+pub const IF_FREQUENCY_MAX: u64 = FXOSC * 31 / (1 << 10);
+
+// f_if = f_osc / 2^10 * FREQ_IF
+pub const fn from_if_frequency(hz: u64) -> u8 {
+    ((hz.rotate_left(10) + FXOSC / 2) / FXOSC) as u8
+}
+
+pub const fn to_if_frequency(freq_if: u8) -> u64 {
+    (FXOSC * freq_if as u64) >> 10
+}
+
+/// Largest frequency offset representable by FSCTRL0, in Hz.
+pub const FREQOFF_MAX: i32 = (FXOSC * 127 / (1 << 14)) as i32;
+/// Smallest frequency offset representable by FSCTRL0, in Hz.
+pub const FREQOFF_MIN: i32 = -((FXOSC * 128 / (1 << 14)) as i32);
+
+// f_offset = f_osc / 2^14 * FREQOFF, two's complement
+pub fn from_freq_offset(hz: i32) -> u8 {
+    let scaled = (hz as i64) << 14;
+    let rounding = scaled.signum() * FXOSC as i64 / 2;
+    ((scaled + rounding) / FXOSC as i64) as i8 as u8
+}
+
+pub fn to_freq_offset(freqoff: u8) -> i32 {
+    (freqoff as i8 as i64 * FXOSC as i64 / (1 << 14)) as i32
+}
+
+/// Smallest channel spacing representable by MDMCFG1/MDMCFG0, in Hz.
+pub const CHANSPC_MIN: u64 = FXOSC * 256 / (1 << 18);
+/// Largest channel spacing representable by MDMCFG1/MDMCFG0, in Hz.
+pub const CHANSPC_MAX: u64 = FXOSC * 511 * 8 / (1 << 18);
+
+// f_chanspc = f_osc / 2^18 * (256 + CHANSPC_M) * 2^CHANSPC_E
+pub fn from_chanspc(v: u64) -> (u8, u8) {
+    let mut exponent = 0;
+    loop {
+        let divisor = FXOSC << exponent;
+        let mantissa = (v.rotate_left(18) + divisor / 2) / divisor;
+        if mantissa < 512 || exponent == 3 {
+            return (mantissa.saturating_sub(256) as u8, exponent as u8);
+        }
+        exponent += 1;
+    }
+}
+
+// t_event0 = 750 / f_osc * EVENT0 * 2^(5 * WOR_RES)
+pub fn from_wor_event0(ms: u64) -> (u16, u8) {
+    let mut wor_res = 0;
+    loop {
+        let divisor = 1000 * 750u64.rotate_left(5 * wor_res);
+        let event0 = (ms * FXOSC + divisor / 2) / divisor;
+        if event0 <= 0xffff || wor_res == 3 {
+            return (event0.clamp(1, 0xffff) as u16, wor_res as u8);
+        }
+        wor_res += 1;
+    }
+}
+
+pub fn to_wor_event0(event0: u16, wor_res: u8) -> u64 {
+    event0 as u64 * 1000 * 750u64.rotate_left(5 * wor_res as u32) / FXOSC
+}
+
+// RX timeout as parts per million of t_event0, indexed by WOR_RES and RX_TIME.
+const RX_TIME_PPM: [[u64; 7]; 4] = [
+    [36058, 18029, 9014, 4507, 2254, 1127, 563],
+    [180288, 90144, 45072, 22536, 11268, 5634, 2817],
+    [324519, 162260, 81130, 40565, 20282, 10141, 5071],
+    [468750, 234375, 117188, 58594, 29297, 14648, 7324],
+];
+
+/// Longest RX_TIME timeout not exceeding `timeout_ms`, falling back to the shortest one.
+pub fn from_wor_rx_timeout(timeout_ms: u64, event0_ms: u64, wor_res: u8) -> u8 {
+    let timeout_ppm = timeout_ms * 1_000_000 / event0_ms.max(1);
+    RX_TIME_PPM[wor_res as usize & 0x3].iter().position(|&ppm| ppm <= timeout_ppm).unwrap_or(6)
+        as u8
+}
+
 #[cfg(test)]
 mod tests {
     use crate::lowlevel::convert::*;
@@ -41,9 +169,21 @@ mod tests {
         assert_eq!(from_frequency(918_000_000), (0xC4, 0x4E, 0x23));
     }
 
+    #[test]
+    fn test_frequency_round_trip() {
+        // f_carrier = f_osc / 2^16 * FREQ
+        let step = FXOSC >> 16;
+        for hz in [315_000_000, 433_920_000, 868_300_000, 915_000_000] {
+            let (freq0, freq1, freq2) = from_frequency(hz);
+            let actual = to_frequency(freq0, freq1, freq2);
+            assert!(actual <= hz && hz - actual <= step, "{} -> {}", hz, actual);
+        }
+    }
+
     #[test]
     fn test_deviation() {
         // f_dev = f_osc / 2^17 * (8 + DEVIATION_M) * 2^DEVIATION_E
+        #[allow(clippy::unnecessary_cast)]
         fn calc_rev_dev(dev_m: u8, dev_e: u8) -> u64 {
             (((FXOSC as f32 / (2u64.pow(17) as f32)) as f32)
                 * (8f32 + dev_m as f32)
@@ -92,6 +232,8 @@ mod tests {
 
     #[test]
     fn test_chanbw() {
+        assert_eq!(from_chanbw(CHANBW_MAX), (0b00, 0b00));
+        assert_eq!(from_chanbw(CHANBW_MIN), (0b11, 0b11));
         assert_eq!(from_chanbw(812500), (0b00, 0b00));
         assert_eq!(from_chanbw(650000), (0b01, 0b00));
         assert_eq!(from_chanbw(541666), (0b10, 0b00));
@@ -109,4 +251,114 @@ mod tests {
         assert_eq!(from_chanbw(67708), (0b10, 0b11));
         assert_eq!(from_chanbw(58035), (0b11, 0b11));
     }
+
+    #[test]
+    fn test_drate_round_trip() {
+        for baud in [
+            DATA_RATE_MIN,
+            1200,
+            2400,
+            4800,
+            9600,
+            38400,
+            76800,
+            100_000,
+            250_000,
+            500_000,
+            DATA_RATE_MAX,
+        ] {
+            let (mantissa, exponent) = from_drate(baud);
+            let actual = to_drate(mantissa, exponent);
+            let step = (FXOSC << exponent) >> 28;
+            assert!(actual.abs_diff(baud) <= step, "{} -> {}", baud, actual);
+        }
+    }
+
+    #[test]
+    fn test_deviation_round_trip() {
+        assert_eq!((DEVIATION_MIN, DEVIATION_MAX), (1587, 380_859));
+        for dev in [1587, 5157, 20_000, 47_607, 95_000, 380_859] {
+            let (mantissa, exponent) = from_deviation(dev);
+            let actual = to_deviation(mantissa, exponent);
+            let step = (FXOSC << exponent) >> 17;
+            assert!(actual.abs_diff(dev) <= step, "{} -> {}", dev, actual);
+        }
+    }
+
+    #[test]
+    fn test_chanbw_round_trip() {
+        for m in 0..4 {
+            for e in 0..4 {
+                assert_eq!(from_chanbw(to_chanbw(m, e)), (m, e));
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_channel_bandwidth() {
+        // SmartRF Studio GFSK 38.4 kBaud, 20 kHz deviation, 101 kHz channel filter
+        assert_eq!(min_channel_bandwidth(38_400, 20_000), 78_400);
+        assert!(to_chanbw(0b00, 0b11) >= min_channel_bandwidth(38_383, 20_629));
+        assert_eq!(min_channel_bandwidth(1_200, 0), 1_200);
+    }
+
+    #[test]
+    fn test_if_frequency() {
+        // SmartRF Studio default, 152.34375 kHz
+        assert_eq!(from_if_frequency(152_343), 0x06);
+        assert_eq!(to_if_frequency(0x06), 152_343);
+        assert_eq!(from_if_frequency(203_125), 0x08);
+        assert_eq!(from_if_frequency(IF_FREQUENCY_MAX), 31);
+        assert_eq!(from_if_frequency(0), 0);
+    }
+
+    #[test]
+    fn test_freq_offset() {
+        assert_eq!(from_freq_offset(0), 0x00);
+        assert_eq!(from_freq_offset(700), 0x00);
+        assert_eq!(from_freq_offset(-700), 0x00);
+        assert_eq!(from_freq_offset(1587), 0x01);
+        assert_eq!(from_freq_offset(-1587), 0xFF);
+        assert_eq!(from_freq_offset(FREQOFF_MAX), 0x7F);
+        assert_eq!(from_freq_offset(FREQOFF_MIN), 0x80);
+        assert_eq!(to_freq_offset(0x00), 0);
+        assert_eq!(to_freq_offset(0x01), 1586);
+        assert_eq!(to_freq_offset(0xFF), -1586);
+        assert_eq!(to_freq_offset(0x80), FREQOFF_MIN);
+    }
+
+    #[test]
+    fn test_chanspc() {
+        // Reset value, 199.951172 kHz
+        assert_eq!(from_chanspc(199951), (0xF8, 2));
+        assert_eq!(from_chanspc(CHANSPC_MIN), (0, 0));
+        assert_eq!(from_chanspc(CHANSPC_MAX), (255, 3));
+        assert_eq!(from_chanspc(49987), (248, 0));
+        assert_eq!(from_chanspc(99975), (248, 1));
+        assert_eq!(from_chanspc(50781), (0, 1));
+        assert_eq!(from_chanspc(350000), (185, 3));
+    }
+
+    #[test]
+    fn test_wor_event0() {
+        // Reset value, 1 s
+        assert_eq!(from_wor_event0(1000), (0x876B, 0));
+        assert_eq!(from_wor_event0(100), (3467, 0));
+        assert_eq!(from_wor_event0(10_000), (10833, 1));
+        assert_eq!(from_wor_event0(0), (1, 0));
+
+        assert_eq!(to_wor_event0(0x876B, 0), 1000);
+        assert_eq!(to_wor_event0(3467, 0), 100);
+        assert_eq!(to_wor_event0(10833, 1), 9999);
+    }
+
+    #[test]
+    fn test_wor_rx_timeout() {
+        assert_eq!(from_wor_rx_timeout(37, 1000, 0), 0);
+        assert_eq!(from_wor_rx_timeout(36, 1000, 0), 1);
+        assert_eq!(from_wor_rx_timeout(10, 1000, 0), 2);
+        assert_eq!(from_wor_rx_timeout(1, 1000, 0), 6);
+        assert_eq!(from_wor_rx_timeout(1000, 1000, 0), 0);
+        assert_eq!(from_wor_rx_timeout(100, 1000, 1), 1);
+    }
 }
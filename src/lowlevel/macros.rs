@@ -18,7 +18,7 @@ macro_rules! register {
                 pub fn $bitfield(&self) -> $uxx {
                     use crate::lowlevel::traits::OffsetSize;
 
-                    let size = $range.size() + 1;
+                    let size = $range.size();
                     let offset = $range.offset();
                     (((1 << size) - 1) as u8) << offset
                 }
@@ -47,7 +47,7 @@ macro_rules! register {
                     use crate::lowlevel::traits::OffsetSize;
 
                     let offset = $range.offset();
-                    let size = $range.size() + 1;
+                    let size = $range.size();
                     let mask = ((1 << size) - 1) as u8;
 
                     (self.bits >> offset) & mask
@@ -66,7 +66,7 @@ macro_rules! register {
                     use crate::lowlevel::traits::OffsetSize;
 
                     let offset = $range.offset();
-                    let size = $range.size() + 1;
+                    let size = $range.size();
                     let mask = ((1 << size) - 1) as u8;
 
                     debug_assert!(bits <= mask);
@@ -1,7 +1,9 @@
 //! Low level unrestricted access to the CC1101 radio chip.
-use hal::blocking::spi::{Transfer, Write, WriteIter};
-use hal::digital::v2::OutputPin;
-use heapless::Vec;
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+use hal::digital::{self, OutputPin};
+use hal::spi::{Operation, SpiDevice};
 #[macro_use]
 mod macros;
 mod access;
@@ -15,68 +17,154 @@ use self::registers::*;
 
 pub const FXOSC: u64 = 26_000_000;
 
-pub struct Cc1101<SPI, CS, GDO2> {
+pub struct Cc1101<SPI, CS, GDO0, GDO2> {
     pub(crate) spi: SPI,
     pub(crate) cs: CS,
-    // gdo0: GDO0,
+    pub(crate) gdo0: GDO0,
     pub(crate) gdo2: GDO2,
+    pub(crate) status: Option<u8>,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<SpiE, GpioE> {
     Spi(SpiE),
     Gpio(GpioE),
 }
 
-impl<SPI, CS, GDO2, SpiE, GpioE> Cc1101<SPI, CS, GDO2>
+impl<SpiE: fmt::Display, GpioE: fmt::Display> fmt::Display for Error<SpiE, GpioE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Spi(e) => write!(f, "SPI error: {}", e),
+            Error::Gpio(e) => write!(f, "GPIO error: {}", e),
+        }
+    }
+}
+
+/// Stand-in CS pin for an `SpiDevice` that manages CS itself.
+pub struct NoCs<E = Infallible>(PhantomData<E>);
+
+impl<E> NoCs<E> {
+    pub fn new() -> Self {
+        NoCs(PhantomData)
+    }
+}
+
+impl<E> Default for NoCs<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: digital::Error> digital::ErrorType for NoCs<E> {
+    type Error = E;
+}
+
+impl<E: digital::Error> OutputPin for NoCs<E> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<SPI, CS, GDO0, GDO2> Cc1101<SPI, CS, GDO0, GDO2> {
+    /// Destroy the driver, returning the SPI device and pins.
+    pub fn release(self) -> (SPI, CS, GDO0, GDO2) {
+        (self.spi, self.cs, self.gdo0, self.gdo2)
+    }
+}
+
+impl<SPI, CS, GDO0, GDO2, SpiE, GpioE> Cc1101<SPI, CS, GDO0, GDO2>
 where
-    SPI: Transfer<u8, Error = SpiE> + Write<u8, Error = SpiE>,
+    SPI: SpiDevice<u8, Error = SpiE>,
     CS: OutputPin<Error = GpioE>,
 {
-    pub fn new(spi: SPI, cs: CS, gdo2: GDO2) -> Result<Self, Error<SpiE, GpioE>> {
+    pub fn new(spi: SPI, cs: CS, gdo0: GDO0, gdo2: GDO2) -> Result<Self, Error<SpiE, GpioE>> {
         let cc1101 = Cc1101 {
             spi,
             cs,
+            gdo0,
             gdo2,
+            status: None,
         };
 
         Ok(cc1101)
     }
 
+    /// Run `operations` as a single SPI transaction, within the separate CS pin if any.
+    pub fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Error<SpiE, GpioE>> {
+        self.cs.set_low().map_err(Error::Gpio)?;
+        let result = self.spi.transaction(operations);
+        self.cs.set_high().map_err(Error::Gpio)?;
+        result.map_err(Error::Spi)
+    }
+
     pub fn read_register<R>(&mut self, reg: R) -> Result<u8, Error<SpiE, GpioE>>
     where
         R: Into<Register>,
     {
-        self.cs.set_low().map_err(Error::Gpio)?;
         let mut buffer = [reg.into().raddr(), 0u8];
-        self.spi.transfer(&mut buffer).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::Gpio)?;
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
+        Ok(buffer[1])
+    }
+
+    /// Read the register at `addr`, setting the burst bit for the status registers (0x30-0x3D).
+    pub fn read_register_raw(&mut self, addr: u8) -> Result<u8, Error<SpiE, GpioE>> {
+        let header = match addr & 0x3F {
+            a @ 0x30..=0x3D => 0x80 | Command::BURSTFLG.addr() | a,
+            a => 0x80 | a,
+        };
+        let mut buffer = [header, 0u8];
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
         Ok(buffer[1])
     }
 
+    /// Write `byte` to the register at `addr`.
+    pub fn write_register_raw(&mut self, addr: u8, byte: u8) -> Result<(), Error<SpiE, GpioE>> {
+        debug_assert!(!is_strobe(addr), "write to strobe address {:#04X}", addr & 0x3F);
+        let mut buffer = [addr & 0x3F, byte];
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
+        Ok(())
+    }
+
+    /// Read the length and address bytes of a packet from the RX FIFO, followed by the rest of
+    /// the packet, up to the size of `buf`.
+    ///
+    /// Returns the number of bytes read into `buf`, less than `len - 1` if it is too small.
     pub fn read_fifo(
         &mut self,
         addr: &mut u8,
         len: &mut u8,
         buf: &mut [u8],
-    ) -> Result<(), Error<SpiE, GpioE>> {
+    ) -> Result<usize, Error<SpiE, GpioE>> {
         let mut buffer = [Command::FIFO.addr() | 0xC0, 0, 0];
 
-        self.cs.set_low().map_err(Error::Gpio)?;
-        self.spi.transfer(&mut buffer).map_err(Error::Spi)?;
-        self.spi.transfer(buf).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::Gpio)?;
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
 
         *len = buffer[1];
         *addr = buffer[2];
 
-        Ok(())
+        let count = (*len as usize).saturating_sub(1).min(buf.len());
+        if count > 0 {
+            self.read_burst(Command::FIFO, &mut buf[..count])?;
+        }
+        Ok(count)
     }
 
     pub fn write_strobe(&mut self, com: Command) -> Result<(), Error<SpiE, GpioE>> {
-        self.cs.set_low().map_err(Error::Gpio)?;
-        self.spi.write(&[com.addr()]).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::Gpio)?;
+        let mut buffer = [com.addr()];
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
         Ok(())
     }
 
@@ -84,29 +172,108 @@ where
     where
         R: Into<Register>,
     {
-        self.cs.set_low().map_err(Error::Gpio)?;
-        self.spi.write(&mut [reg.into().waddr(), byte]).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::Gpio)?;
+        let addr = reg.into().waddr();
+        debug_assert!(!is_strobe(addr), "write to strobe address {:#04X}", addr & 0x3F);
+        let mut buffer = [addr, byte];
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer)])?;
+        self.status = Some(buffer[0]);
         Ok(())
     }
 
-    pub fn write_burst<R>(&mut self, reg: R, bytes: &mut [u8]) -> Result<(), Error<SpiE, GpioE>>
+    /// Write `bytes` to consecutive registers starting at `reg`, or to a FIFO, in one
+    /// transaction.
+    pub fn write_burst<R>(&mut self, reg: R, bytes: &[u8]) -> Result<(), Error<SpiE, GpioE>>
     where
         R: Into<Register>,
     {
-        // let mut payload: Vec<u8, 64> = Vec::new();
-        // payload.push(reg.into().waddr() | Command::BURSTFLG.addr());
-        // payload.extend(bytes.iter().cloned());
+        let mut buffer = [reg.into().waddr() | Command::BURSTFLG.addr()];
 
-        let mut payload_u8: [u8; 64] = [0; 64];
-        payload_u8[0] = reg.into().waddr() | Command::BURSTFLG.addr();
-        payload_u8[1..bytes.len() + 1].copy_from_slice(&bytes);
-        self.cs.set_low().map_err(Error::Gpio)?;
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer), Operation::Write(bytes)])?;
+        self.status = Some(buffer[0]);
+        Ok(())
+    }
 
-        self.spi.write(&mut payload_u8[..bytes.len()]).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::Gpio)?;
+    /// Write the bytes of `bytes` to consecutive registers starting at `reg`, or to a FIFO,
+    /// without collecting them into a buffer first.
+    ///
+    /// The bytes are written in bursts of up to 16 bytes, each in its own transaction.
+    pub fn write_burst_iter<R, I>(&mut self, reg: R, bytes: I) -> Result<(), Error<SpiE, GpioE>>
+    where
+        R: Into<Register>,
+        I: IntoIterator<Item = u8>,
+    {
+        let mut addr = reg.into().waddr();
+        let mut bytes = bytes.into_iter().peekable();
+        while bytes.peek().is_some() {
+            let mut chunk = [0u8; 16];
+            let mut len = 0;
+            for (slot, byte) in chunk.iter_mut().zip(&mut bytes) {
+                *slot = byte;
+                len += 1;
+            }
+            let mut buffer = [addr | Command::BURSTFLG.addr()];
+            self.transaction(&mut [
+                Operation::TransferInPlace(&mut buffer),
+                Operation::Write(&chunk[..len]),
+            ])?;
+            self.status = Some(buffer[0]);
+            // The FIFOs are written through a single address.
+            if addr != Command::FIFO.addr() {
+                addr += len as u8;
+            }
+        }
         Ok(())
     }
+
+    /// Read `buf.len()` consecutive registers starting at `reg`, in one transaction.
+    ///
+    /// Burst reads are only valid for the configuration registers, the RX FIFO and the PATABLE.
+    /// The status registers have no burst access, so must be read one at a time with
+    /// [`read_register`](Self::read_register).
+    pub fn read_burst<R>(&mut self, reg: R, buf: &mut [u8]) -> Result<(), Error<SpiE, GpioE>>
+    where
+        R: Into<Register>,
+    {
+        let addr = reg.into().raddr();
+        debug_assert!(
+            !is_strobe(addr) || buf.len() <= 1,
+            "burst read of status register {:#04X}",
+            addr & 0x3F
+        );
+        let mut buffer = [addr | Command::BURSTFLG.addr()];
+
+        self.transaction(&mut [
+            Operation::TransferInPlace(&mut buffer),
+            Operation::TransferInPlace(buf),
+        ])?;
+        self.status = Some(buffer[0]);
+        Ok(())
+    }
+
+    pub fn read_patable(&mut self, out: &mut [u8; 8]) -> Result<(), Error<SpiE, GpioE>> {
+        let mut buffer = [Command::PATABLE.addr() | 0xC0];
+
+        self.transaction(&mut [
+            Operation::TransferInPlace(&mut buffer),
+            Operation::TransferInPlace(out),
+        ])?;
+        self.status = Some(buffer[0]);
+        Ok(())
+    }
+
+    pub fn write_patable(&mut self, table: &[u8; 8]) -> Result<(), Error<SpiE, GpioE>> {
+        let mut buffer = [Command::PATABLE.addr() | Command::BURSTFLG.addr()];
+
+        self.transaction(&mut [Operation::TransferInPlace(&mut buffer), Operation::Write(table)])?;
+        self.status = Some(buffer[0]);
+        Ok(())
+    }
+
+    /// Status byte returned by the chip at the start of the most recent access, if any.
+    pub fn last_status(&self) -> Option<u8> {
+        self.status
+    }
+
     pub fn modify_register<R, F>(&mut self, reg: R, f: F) -> Result<(), Error<SpiE, GpioE>>
     where
         R: Into<Register> + Copy,
@@ -117,3 +284,130 @@ where
         Ok(())
     }
 }
+
+// Writing to 0x30-0x3D issues a command strobe, these addresses only hold read-only status
+// registers when accessed in burst.
+fn is_strobe(addr: u8) -> bool {
+    (0x30..=0x3D).contains(&(addr & 0x3F))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use crate::mock;
+
+    #[test]
+    fn test_write_patable() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let ramp = [0x00, 0x12, 0x0E, 0x34, 0x60, 0x84, 0xC8, 0xC0];
+        cc1101.write_patable(&ramp).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.transactions, [[0x7E, 0x00, 0x12, 0x0E, 0x34, 0x60, 0x84, 0xC8, 0xC0]]);
+        assert_eq!(chip.patable, ramp);
+    }
+
+    #[test]
+    fn test_write_burst() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let payload = [0x01, 0x02, 0x03, 0x04, 0x05];
+        cc1101.write_burst(super::Command::FIFO, &payload).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.transactions, [[0x7F, 0x01, 0x02, 0x03, 0x04, 0x05]]);
+        assert_eq!(chip.tx_fifo, payload);
+    }
+
+    #[test]
+    fn test_write_burst_iter() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.write_burst_iter(super::Command::FIFO, 0..20).unwrap();
+        cc1101.write_burst_iter(super::Config::IOCFG2, (0..18).map(|i| i + 0x80)).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.tx_fifo, (0..20).collect::<std::vec::Vec<u8>>());
+        assert_eq!(chip.transactions[0][..2], [0x7F, 0]);
+        assert_eq!(chip.transactions[1][..2], [0x7F, 16]);
+        assert_eq!(chip.transactions[3][..2], [0x50, 0x90]);
+        assert_eq!(chip.register(super::Config::IOCFG2), 0x80);
+        assert_eq!(chip.register(super::Config::FIFOTHR), 0x83);
+        assert_eq!(chip.register(super::Config::PKTCTRL1), 0x87);
+    }
+
+    #[test]
+    fn test_read_patable() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let ramp = [0x00, 0x12, 0x0E, 0x34, 0x60, 0x84, 0xC8, 0xC0];
+        chip.borrow_mut().patable = ramp;
+
+        let mut out = [0u8; 8];
+        cc1101.read_patable(&mut out).unwrap();
+
+        assert_eq!(out, ramp);
+        assert_eq!(chip.borrow().transactions, [[0xFE, 0, 0, 0, 0, 0, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_read_burst() {
+        let (chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut config = [0u8; 3];
+        cc1101.read_burst(super::Config::FREQ2, &mut config).unwrap();
+        assert_eq!(config, [0x1E, 0xC4, 0xEC]);
+
+        chip.borrow_mut().status[0x03] = 0x80;
+        let mut status = [0u8; 1];
+        cc1101.read_burst(super::Status::LQI, &mut status).unwrap();
+        assert_eq!(status, [0x80]);
+
+        let chip = chip.borrow();
+        assert_eq!(chip.transactions[0], [0xCD, 0, 0, 0]);
+        assert_eq!(chip.transactions[1], [0xF3, 0]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "burst read of status register 0x33")]
+    fn test_read_burst_status() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        let mut status = [0u8; 2];
+        cc1101.read_burst(super::Status::LQI, &mut status).unwrap();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "write to strobe address 0x36")]
+    fn test_write_register_raw_strobe() {
+        let (_chip, spi, cs, gdo0, gdo2) = mock::chip();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.write_register_raw(0x36, 0x00).unwrap();
+    }
+
+    #[test]
+    fn test_no_cs() {
+        let (chip, spi, _cs, gdo0, gdo2) = mock::chip();
+        let cs = super::NoCs::<core::convert::Infallible>::new();
+        let mut cc1101 = super::Cc1101::new(spi, cs, gdo0, gdo2).unwrap();
+
+        cc1101.write_strobe(super::Command::SIDLE).unwrap();
+        cc1101.write_register(super::Config::CHANNR, 0x05).unwrap();
+
+        let chip = chip.borrow();
+        assert_eq!(chip.transactions[0], [0x36]);
+        assert_eq!(chip.transactions[1], [0x0A, 0x05]);
+        assert_eq!(chip.register(super::Config::CHANNR), 0x05);
+    }
+}
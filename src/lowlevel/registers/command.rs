@@ -43,6 +43,7 @@ impl Command {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<crate::lowlevel::registers::Register> for Command {
     fn into(self) -> crate::lowlevel::registers::Register {
         crate::lowlevel::registers::Register::Command(self)
@@ -102,6 +102,7 @@ impl Config {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<crate::lowlevel::registers::Register> for Config {
     fn into(self) -> crate::lowlevel::registers::Register {
         crate::lowlevel::registers::Register::Config(self)
@@ -37,6 +37,7 @@ impl Status {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<crate::lowlevel::registers::Register> for Status {
     fn into(self) -> crate::lowlevel::registers::Register {
         crate::lowlevel::registers::Register::Status(self)
@@ -21,7 +21,7 @@ impl OffsetSize for Range<u8> {
     }
 
     fn size(self) -> u8 {
-        self.end - self.start
+        self.end - self.start + 1
     }
 }
 
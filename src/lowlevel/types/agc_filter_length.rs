@@ -0,0 +1,15 @@
+/// Channel filter samples averaged by the AGC, for 2-FSK, 4-FSK, GFSK and MSK.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum AgcFilterLength {
+    SAMPLES_8 = 0x00,
+    SAMPLES_16 = 0x01,
+    SAMPLES_32 = 0x02,
+    SAMPLES_64 = 0x03,
+}
+
+impl AgcFilterLength {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,15 @@
+/// OOK/ASK decision boundary, the amplitude step between a 0 and a 1.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum AskDecisionBoundary {
+    DB_4 = 0x00,
+    DB_8 = 0x01,
+    DB_12 = 0x02,
+    DB_16 = 0x03,
+}
+
+impl AskDecisionBoundary {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,19 @@
+/// Clear channel indication configuration.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum CcaCheck {
+    /// Always.
+    ALWAYS = 0x00,
+    /// If RSSI below threshold.
+    RSSI_BELOW_THR = 0x01,
+    /// Unless currently receiving a packet.
+    UNLESS_RECEIVING = 0x02,
+    /// If RSSI below threshold unless currently receiving a packet.
+    RSSI_BELOW_THR_UNLESS_RECEIVING = 0x03,
+}
+
+impl CcaCheck {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,15 @@
+/// RX attenuation for close-in reception, see DN010.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum CloseInRx {
+    DB_0 = 0x00,
+    DB_6 = 0x01,
+    DB_12 = 0x02,
+    DB_18 = 0x03,
+}
+
+impl CloseInRx {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
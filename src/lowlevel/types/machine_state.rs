@@ -1,6 +1,8 @@
+use core::convert::TryFrom;
+
 /// Radio hardware machine states.
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MachineState {
     SLEEP = 0x00,
     IDLE = 0x01,
@@ -32,3 +34,38 @@ impl MachineState {
         *self as u8
     }
 }
+
+impl TryFrom<u8> for MachineState {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use MachineState::*;
+
+        const STATES: [MachineState; 23] = [
+            SLEEP,
+            IDLE,
+            XOFF,
+            VCOON_MC,
+            REGON_MC,
+            MANCAL,
+            VCOON,
+            REGON,
+            STARTCAL,
+            BWBOOST,
+            FS_LOCK,
+            IFADCON,
+            ENDCAL,
+            RX,
+            RX_END,
+            RX_RST,
+            TXRX_SWITCH,
+            RXFIFO_OVERFLOW,
+            FSTXON,
+            TX,
+            TX_END,
+            RXTX_SWITCH,
+            TXFIFO_UNDERFLOW,
+        ];
+        STATES.get(value as usize).copied().ok_or(value)
+    }
+}
@@ -0,0 +1,19 @@
+/// Target amplitude from the digital channel filter.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum MagnTarget {
+    DB_24 = 0x00,
+    DB_27 = 0x01,
+    DB_30 = 0x02,
+    DB_33 = 0x03,
+    DB_36 = 0x04,
+    DB_38 = 0x05,
+    DB_40 = 0x06,
+    DB_42 = 0x07,
+}
+
+impl MagnTarget {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
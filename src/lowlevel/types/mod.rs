@@ -1,21 +1,35 @@
 mod address_check;
+mod agc_filter_length;
+mod ask_decision_boundary;
 mod auto_calibration;
+mod cca_check;
+mod close_in_rx;
 mod fifo_threshold;
 mod gdo_cfg;
 mod length_config;
 mod machine_state;
+mod magn_target;
 mod mod_format;
 mod num_preamble;
 mod po_timeout;
+mod rx_off_mode;
 mod sync_check;
+mod tx_off_mode;
 
 pub use self::address_check::*;
+pub use self::agc_filter_length::*;
+pub use self::ask_decision_boundary::*;
 pub use self::auto_calibration::*;
+pub use self::cca_check::*;
+pub use self::close_in_rx::*;
 pub use self::fifo_threshold::*;
 pub use self::gdo_cfg::*;
 pub use self::length_config::*;
 pub use self::machine_state::*;
+pub use self::magn_target::*;
 pub use self::mod_format::*;
 pub use self::num_preamble::*;
 pub use self::po_timeout::*;
+pub use self::rx_off_mode::*;
 pub use self::sync_check::*;
+pub use self::tx_off_mode::*;
@@ -0,0 +1,19 @@
+/// State to enter when a packet has been received.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum RxOffMode {
+    /// Enter IDLE.
+    IDLE = 0x00,
+    /// Enter FSTXON.
+    FSTXON = 0x01,
+    /// Enter TX.
+    TX = 0x02,
+    /// Stay in RX.
+    RX = 0x03,
+}
+
+impl RxOffMode {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
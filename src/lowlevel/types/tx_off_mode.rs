@@ -0,0 +1,19 @@
+/// State to enter when a packet has been sent.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub enum TxOffMode {
+    /// Enter IDLE.
+    IDLE = 0x00,
+    /// Enter FSTXON.
+    FSTXON = 0x01,
+    /// Stay in TX, start sending preamble.
+    TX = 0x02,
+    /// Enter RX.
+    RX = 0x03,
+}
+
+impl TxOffMode {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
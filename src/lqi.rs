@@ -0,0 +1,6 @@
+const LQI_MAX: u16 = 127;
+
+pub fn lqi_to_percent(lqi: u8) -> u8 {
+    // LQI is 7 bits, lower is better
+    ((LQI_MAX - (lqi as u16).min(LQI_MAX)) * 100 / LQI_MAX) as u8
+}
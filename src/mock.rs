@@ -0,0 +1,401 @@
+//! Simulated CC1101 behind mock SPI and GPIO implementations, for unit tests.
+extern crate std;
+
+use core::convert::Infallible;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use hal::delay::DelayNs;
+use hal::digital::{self, InputPin, OutputPin};
+use hal::spi::{self, Operation, SpiDevice};
+
+use crate::lowlevel::registers::Config;
+
+/// Register file and FIFOs of the simulated chip.
+pub struct Chip {
+    pub config: [u8; 0x2F],
+    pub status: [u8; 0x0E],
+    pub patable: [u8; 8],
+    pub marcstate: u8,
+    /// Calibration fails to lock the PLL, leaving the synthesizer stuck when entering RX or TX.
+    pub pll_unlocked: bool,
+    /// Levels of the GDO0, GDO1 and GDO2 pins.
+    pub gdo: [bool; 3],
+    /// Async waits on the GDO pins, each of which needs the chip to drive the pin.
+    #[cfg(feature = "async")]
+    pub gdo_waits: usize,
+    pub rx_fifo: VecDeque<u8>,
+    /// Bytes on air, received into the RX FIFO while receiving.
+    pub rx_air: VecDeque<u8>,
+    pub tx_fifo: Vec<u8>,
+    /// Bytes drained from the TX FIFO while transmitting.
+    pub sent: Vec<u8>,
+    /// Bytes clocked out on MOSI, one entry per SPI transaction.
+    pub transactions: Vec<Vec<u8>>,
+    /// Level of the separate CS pin.
+    pub cs_low: bool,
+    /// SPI transactions left reporting CHIP_RDYn high, while the crystal oscillator starts.
+    pub not_ready: u32,
+    access: Option<Access>,
+    patable_index: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Access {
+    addr: u8,
+    read: bool,
+    burst: bool,
+}
+
+impl Chip {
+    fn new() -> Self {
+        let mut chip = Chip {
+            config: [0; 0x2F],
+            status: [0; 0x0E],
+            patable: [0xC6, 0, 0, 0, 0, 0, 0, 0],
+            marcstate: 0x01,
+            pll_unlocked: false,
+            gdo: [false; 3],
+            #[cfg(feature = "async")]
+            gdo_waits: 0,
+            rx_fifo: VecDeque::new(),
+            rx_air: VecDeque::new(),
+            tx_fifo: Vec::new(),
+            sent: Vec::new(),
+            transactions: Vec::new(),
+            cs_low: false,
+            not_ready: 0,
+            access: None,
+            patable_index: 0,
+        };
+        chip.reset();
+        chip
+    }
+
+    /// Current value of a configuration register.
+    pub fn register(&self, reg: Config) -> u8 {
+        self.config[reg.addr() as usize]
+    }
+
+    fn reset(&mut self) {
+        self.config = RESET_VALUES;
+        self.patable = [0xC6, 0, 0, 0, 0, 0, 0, 0];
+        self.marcstate = 0x01;
+        self.rx_fifo.clear();
+        self.tx_fifo.clear();
+    }
+
+    fn begin(&mut self) {
+        self.transactions.push(Vec::new());
+        self.access = None;
+        self.patable_index = 0;
+    }
+
+    fn end(&mut self) {
+        self.not_ready = self.not_ready.saturating_sub(1);
+        self.access = None;
+    }
+
+    fn chip_status(&self) -> u8 {
+        let state = match self.marcstate {
+            0x01 => 0,
+            0x0D..=0x0F => 1,
+            0x13..=0x15 => 2,
+            0x12 => 3,
+            0x11 => 6,
+            0x16 => 7,
+            _ => 4,
+        };
+        let chip_rdyn = if self.not_ready > 0 {
+            0x80
+        } else {
+            0
+        };
+        chip_rdyn | (state << 4) | (self.rx_fifo.len().min(15) as u8)
+    }
+
+    fn strobe(&mut self, addr: u8) {
+        match addr {
+            0x30 => self.reset(),
+            0x31 => self.marcstate = 0x12,
+            0x33 | 0x36 => self.marcstate = 0x01,
+            0x34 | 0x35 if self.pll_unlocked => {
+                self.config[Config::FSCAL1.addr() as usize] = 0x3F;
+                self.marcstate = 0x0A;
+            }
+            0x34 => self.marcstate = 0x0D,
+            0x35 => self.marcstate = 0x13,
+            0x3A => {
+                self.rx_fifo.clear();
+                if self.marcstate == 0x11 {
+                    self.marcstate = 0x01;
+                }
+            }
+            0x3B => {
+                self.tx_fifo.clear();
+                if self.marcstate == 0x16 {
+                    self.marcstate = 0x01;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Complete a transmission, entering the state selected by MCSM1.TXOFF_MODE.
+    fn finish_tx(&mut self) {
+        self.marcstate = match self.register(Config::MCSM1) & 0x03 {
+            0 => 0x01,
+            1 => 0x12,
+            2 => 0x13,
+            _ => 0x0D,
+        };
+    }
+
+    fn read(&mut self, addr: u8, burst: bool) -> u8 {
+        match addr {
+            0x00..=0x2E => self.config[addr as usize],
+            0x30..=0x3D if burst => match addr {
+                0x35 => {
+                    let marcstate = self.marcstate;
+                    if marcstate == 0x13 {
+                        self.finish_tx();
+                    }
+                    marcstate
+                }
+                0x3A => {
+                    // Transmitting drains the FIFO.
+                    if self.marcstate == 0x13 {
+                        self.sent.append(&mut self.tx_fifo);
+                    }
+                    let underflow = if self.marcstate == 0x16 {
+                        0x80
+                    } else {
+                        0
+                    };
+                    underflow | self.tx_fifo.len() as u8
+                }
+                0x3B => {
+                    // Receiving fills the FIFO from the air.
+                    if self.marcstate == 0x0D {
+                        let count = self.rx_air.len().min(64 - self.rx_fifo.len());
+                        self.rx_fifo.extend(self.rx_air.drain(..count));
+                    }
+                    self.rx_fifo.len() as u8
+                }
+                _ => self.status[(addr - 0x30) as usize],
+            },
+            0x3E => {
+                let value = self.patable[self.patable_index];
+                self.patable_index = (self.patable_index + 1) % 8;
+                value
+            }
+            0x3F => self.rx_fifo.pop_front().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u8, value: u8) {
+        match addr {
+            0x00..=0x2E => self.config[addr as usize] = value,
+            0x3E => {
+                self.patable[self.patable_index] = value;
+                self.patable_index = (self.patable_index + 1) % 8;
+            }
+            0x3F => self.tx_fifo.push(value),
+            _ => {}
+        }
+    }
+
+    fn clock(&mut self, mosi: u8) -> u8 {
+        self.transactions.last_mut().expect("SPI access without CS asserted").push(mosi);
+        match self.access {
+            None => {
+                let access = Access {
+                    addr: mosi & 0x3F,
+                    read: mosi & 0x80 != 0,
+                    burst: mosi & 0x40 != 0,
+                };
+                let is_strobe =
+                    (0x30..=0x3D).contains(&access.addr) && !(access.read && access.burst);
+                if is_strobe {
+                    self.strobe(access.addr);
+                } else {
+                    self.access = Some(access);
+                }
+                self.chip_status()
+            }
+            Some(access) => {
+                let miso = if access.read {
+                    self.read(access.addr, access.burst)
+                } else {
+                    self.write(access.addr, mosi);
+                    self.chip_status()
+                };
+                self.access = match (access.burst, access.addr) {
+                    (false, _) => None,
+                    // Status registers have no burst access, nor do the FIFO and PATABLE
+                    // advance the address.
+                    (true, 0x30..=0x3F) => Some(access),
+                    (true, addr) => Some(Access {
+                        addr: addr + 1,
+                        ..access
+                    }),
+                };
+                miso
+            }
+        }
+    }
+}
+
+const RESET_VALUES: [u8; 0x2F] = [
+    0x29, 0x2E, 0x3F, 0x07, 0xD3, 0x91, 0xFF, 0x04, 0x45, 0x00, 0x00, 0x0F, 0x00, 0x1E, 0xC4, 0xEC,
+    0x8C, 0x22, 0x02, 0x22, 0xF8, 0x47, 0x07, 0x30, 0x04, 0x36, 0x6C, 0x03, 0x40, 0x91, 0x87, 0x6B,
+    0xF8, 0x56, 0x10, 0xA9, 0x0A, 0x20, 0x0D, 0x41, 0x00, 0x59, 0x7F, 0x3F, 0x88, 0x31, 0x0B,
+];
+
+pub type Handle = Rc<RefCell<Chip>>;
+
+pub struct Spi(Handle);
+
+impl spi::ErrorType for Spi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Spi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let mut chip = self.0.borrow_mut();
+        chip.begin();
+        for operation in operations {
+            match operation {
+                Operation::Read(words) => {
+                    for word in words.iter_mut() {
+                        *word = chip.clock(0);
+                    }
+                }
+                Operation::Write(words) => {
+                    for word in words.iter() {
+                        chip.clock(*word);
+                    }
+                }
+                Operation::Transfer(read, write) => {
+                    for i in 0..read.len().max(write.len()) {
+                        let miso = chip.clock(write.get(i).copied().unwrap_or(0));
+                        if let Some(word) = read.get_mut(i) {
+                            *word = miso;
+                        }
+                    }
+                }
+                Operation::TransferInPlace(words) => {
+                    for word in words.iter_mut() {
+                        *word = chip.clock(*word);
+                    }
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        chip.end();
+        Ok(())
+    }
+}
+
+/// Separate CS pin, the simulated `SpiDevice` already frames each transaction.
+pub struct Cs(Handle);
+
+impl digital::ErrorType for Cs {
+    type Error = Infallible;
+}
+
+impl OutputPin for Cs {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().cs_low = true;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().cs_low = false;
+        Ok(())
+    }
+}
+
+pub struct Gdo(Handle, usize);
+
+impl digital::ErrorType for Gdo {
+    type Error = Infallible;
+}
+
+impl InputPin for Gdo {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.0.borrow().gdo[self.1])
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.borrow().gdo[self.1])
+    }
+}
+
+/// Waiting for a level on a pin simulates the chip driving it there.
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for Gdo {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        let mut chip = self.0.borrow_mut();
+        chip.gdo_waits += 1;
+        chip.gdo[self.1] = true;
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        let mut chip = self.0.borrow_mut();
+        chip.gdo_waits += 1;
+        chip.gdo[self.1] = false;
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_high().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_low().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let mut chip = self.0.borrow_mut();
+        chip.gdo_waits += 1;
+        chip.gdo[self.1] = !chip.gdo[self.1];
+        Ok(())
+    }
+}
+
+/// Delay that returns immediately.
+pub struct Delay;
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for Delay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Poll `future` to completion, the simulated chip never leaves it pending.
+#[cfg(feature = "async")]
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use core::task::{Context, Poll, Waker};
+
+    let mut future = core::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Create a simulated chip, and the SPI and pins connected to it.
+pub fn chip() -> (Handle, Spi, Cs, Gdo, Gdo) {
+    let chip = Rc::new(RefCell::new(Chip::new()));
+    (chip.clone(), Spi(chip.clone()), Cs(chip.clone()), Gdo(chip.clone(), 0), Gdo(chip, 2))
+}
@@ -0,0 +1,20 @@
+use crate::Band;
+
+// PATABLE settings from Design Note DN013, Table 3 (optimum PA settings).
+const POWER_LEVELS: [i8; 8] = [-30, -20, -15, -10, 0, 5, 7, 10];
+
+const PATABLE_315: [u8; 8] = [0x12, 0x0D, 0x1C, 0x34, 0x51, 0x85, 0xCB, 0xC2];
+const PATABLE_433: [u8; 8] = [0x12, 0x0E, 0x1D, 0x34, 0x60, 0x84, 0xC8, 0xC0];
+const PATABLE_868: [u8; 8] = [0x03, 0x0F, 0x1E, 0x27, 0x50, 0x81, 0xCB, 0xC2];
+const PATABLE_915: [u8; 8] = [0x03, 0x0E, 0x1E, 0x27, 0x8E, 0xCD, 0xC7, 0xC0];
+
+/// Look up the PATABLE value for an output power level in the given band.
+pub fn dbm_to_patable(band: Band, dbm: i8) -> Option<u8> {
+    let table = match band {
+        Band::Band315 => &PATABLE_315,
+        Band::Band433 => &PATABLE_433,
+        Band::Band868 => &PATABLE_868,
+        Band::Band915 => &PATABLE_915,
+    };
+    POWER_LEVELS.iter().position(|&level| level == dbm).map(|i| table[i])
+}
@@ -0,0 +1,193 @@
+use crate::{AddressFilter, AgcConfig, Modulation, PacketLength, PreambleLength, SyncMode};
+
+/// Radio configuration, written in one pass by `Cc1101::apply`, defaulting to the chip reset
+/// values.
+#[derive(Clone, Copy, Debug)]
+pub struct RadioConfig {
+    pub(crate) frequency: u64,
+    pub(crate) data_rate: u64,
+    pub(crate) deviation: u64,
+    pub(crate) chanbw: u64,
+    pub(crate) modulation: Modulation,
+    pub(crate) sync_mode: SyncMode,
+    pub(crate) preamble: PreambleLength,
+    pub(crate) packet_length: PacketLength,
+    pub(crate) address_filter: AddressFilter,
+    pub(crate) crc: bool,
+    pub(crate) whitening: bool,
+    pub(crate) manchester: bool,
+    pub(crate) dc_filter: bool,
+    pub(crate) tx_power_dbm: Option<i8>,
+    pub(crate) agc: Option<AgcConfig>,
+}
+
+impl Default for RadioConfig {
+    fn default() -> Self {
+        RadioConfig {
+            frequency: 800_000_000,
+            data_rate: 115_051,
+            deviation: 47_607,
+            chanbw: 203_125,
+            modulation: Modulation::BinaryFrequencyShiftKeying,
+            sync_mode: SyncMode::MatchFull(0xD391),
+            preamble: PreambleLength::Bytes4,
+            packet_length: PacketLength::Variable(0xFF),
+            address_filter: AddressFilter::Disabled,
+            crc: true,
+            whitening: true,
+            manchester: false,
+            dc_filter: true,
+            tx_power_dbm: None,
+            agc: None,
+        }
+    }
+}
+
+impl RadioConfig {
+    /// 315 MHz, GFSK, 38.4 kBaud, 20 kHz deviation, 101 kHz channel bandwidth.
+    pub fn preset_315mhz_38k4_gfsk() -> Self {
+        Self::gfsk_38k4(315_000_000)
+    }
+
+    /// 433.92 MHz, GFSK, 38.4 kBaud, 20 kHz deviation, 101 kHz channel bandwidth.
+    pub fn preset_433mhz_38k4_gfsk() -> Self {
+        Self::gfsk_38k4(433_920_000)
+    }
+
+    /// 868.3 MHz, GFSK, 38.4 kBaud, 20 kHz deviation, 101 kHz channel bandwidth.
+    pub fn preset_868mhz_38k4_gfsk() -> Self {
+        Self::gfsk_38k4(868_300_000)
+    }
+
+    /// 915 MHz, GFSK, 38.4 kBaud, 20 kHz deviation, 101 kHz channel bandwidth.
+    pub fn preset_915mhz_38k4_gfsk() -> Self {
+        Self::gfsk_38k4(915_000_000)
+    }
+
+    /// 315 MHz, GFSK, 1.2 kBaud, 5.2 kHz deviation, 58 kHz channel bandwidth.
+    pub fn preset_315mhz_1k2_gfsk() -> Self {
+        Self::gfsk_1k2(315_000_000)
+    }
+
+    /// 433.92 MHz, GFSK, 1.2 kBaud, 5.2 kHz deviation, 58 kHz channel bandwidth.
+    pub fn preset_433mhz_1k2_gfsk() -> Self {
+        Self::gfsk_1k2(433_920_000)
+    }
+
+    /// 868.3 MHz, GFSK, 1.2 kBaud, 5.2 kHz deviation, 58 kHz channel bandwidth.
+    pub fn preset_868mhz_1k2_gfsk() -> Self {
+        Self::gfsk_1k2(868_300_000)
+    }
+
+    /// 915 MHz, GFSK, 1.2 kBaud, 5.2 kHz deviation, 58 kHz channel bandwidth.
+    pub fn preset_915mhz_1k2_gfsk() -> Self {
+        Self::gfsk_1k2(915_000_000)
+    }
+
+    // Modem and AGC settings of the SmartRF Studio GFSK exports, with a 30/32 sync word,
+    // variable length packets with CRC, and no whitening.
+    fn gfsk(frequency: u64, data_rate: u64, deviation: u64, chanbw: u64) -> Self {
+        RadioConfig::default()
+            .frequency(frequency)
+            .data_rate(data_rate)
+            .deviation(deviation)
+            .chanbw(chanbw)
+            .modulation(Modulation::GaussianFrequencyShiftKeying)
+            .sync_mode(SyncMode::MatchPartialRepeated(0xD391))
+            .whitening(false)
+    }
+
+    fn gfsk_38k4(frequency: u64) -> Self {
+        Self::gfsk(frequency, 38_383, 20_629, 101_562).agc(AgcConfig::default().max_dvga_gain(1))
+    }
+
+    fn gfsk_1k2(frequency: u64) -> Self {
+        Self::gfsk(frequency, 1_199, 5_157, 58_035).agc(AgcConfig::default())
+    }
+
+    /// Base carrier frequency, in Hz, see `Cc1101::set_frequency`.
+    pub fn frequency(mut self, hz: u64) -> Self {
+        self.frequency = hz;
+        self
+    }
+
+    /// Data rate, in baud, 0.6 kBaud to 600 kBaud.
+    pub fn data_rate(mut self, baud: u64) -> Self {
+        self.data_rate = baud;
+        self
+    }
+
+    /// Frequency deviation, in Hz, for FSK modulations, 1.6 kHz to 380.9 kHz.
+    pub fn deviation(mut self, hz: u64) -> Self {
+        self.deviation = hz;
+        self
+    }
+
+    /// Receiver channel filter bandwidth, in Hz, 58 kHz to 812.5 kHz.
+    pub fn chanbw(mut self, hz: u64) -> Self {
+        self.chanbw = hz;
+        self
+    }
+
+    pub fn modulation(mut self, format: Modulation) -> Self {
+        self.modulation = format;
+        self
+    }
+
+    pub fn sync_mode(mut self, sync_mode: SyncMode) -> Self {
+        self.sync_mode = sync_mode;
+        self
+    }
+
+    /// Minimum number of preamble bytes to transmit.
+    pub fn preamble(mut self, pre: PreambleLength) -> Self {
+        self.preamble = pre;
+        self
+    }
+
+    pub fn packet_length(mut self, length: PacketLength) -> Self {
+        self.packet_length = length;
+        self
+    }
+
+    pub fn address_filter(mut self, filter: AddressFilter) -> Self {
+        self.address_filter = filter;
+        self
+    }
+
+    /// CRC calculation in TX and check in RX.
+    pub fn crc(mut self, enabled: bool) -> Self {
+        self.crc = enabled;
+        self
+    }
+
+    /// Data whitening, which both ends of the link must agree on.
+    pub fn whitening(mut self, enabled: bool) -> Self {
+        self.whitening = enabled;
+        self
+    }
+
+    /// Manchester encoding and decoding.
+    pub fn manchester(mut self, enabled: bool) -> Self {
+        self.manchester = enabled;
+        self
+    }
+
+    /// Digital DC blocking filter, see `Cc1101::set_dc_filter`.
+    pub fn dc_filter(mut self, enabled: bool) -> Self {
+        self.dc_filter = enabled;
+        self
+    }
+
+    /// Output power, in dBm, see `Cc1101::set_tx_power_dbm`. The PATABLE is left as is if unset.
+    pub fn tx_power_dbm(mut self, dbm: i8) -> Self {
+        self.tx_power_dbm = Some(dbm);
+        self
+    }
+
+    /// Automatic Gain Control configuration, left as is if unset.
+    pub fn agc(mut self, cfg: AgcConfig) -> Self {
+        self.agc = Some(cfg);
+        self
+    }
+}
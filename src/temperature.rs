@@ -0,0 +1,7 @@
+// Typical temperature sensor characteristics, see datasheet Table 31.
+const VOLTAGE_AT_0C_MV: i32 = 747;
+const SLOPE_UV_PER_C: i32 = 2470;
+
+pub fn mv_to_celsius(mv: u16) -> i16 {
+    ((mv as i32 - VOLTAGE_AT_0C_MV) * 1000 / SLOPE_UV_PER_C) as i16
+}